@@ -0,0 +1,119 @@
+//! The derive macro of the `deta` crate.
+//!
+//! Use it through the `derive` feature of `deta`, as `deta::DetaItem`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Error, Field, Fields, Lit, Meta};
+use syn::{NestedMeta, Result};
+
+/// Derives `deta::DetaItem`, storing the field marked with `#[deta(key)]` as the key of the item.
+///
+/// The struct must also implement `Serialize` and `Deserialize`.
+/// If the key field is renamed with `#[serde(rename = "...")]`, the new name is used.
+#[proc_macro_derive(DetaItem, attributes(deta))]
+pub fn derive_deta_item(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(&input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(x) => match &x.fields {
+            Fields::Named(x) => &x.named,
+            _ => return Err(Error::new_spanned(input, "DetaItem needs named fields")),
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                input,
+                "DetaItem can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut key = None;
+
+    for field in fields {
+        if !is_key(field)? {
+            continue;
+        }
+
+        if key.is_some() {
+            return Err(Error::new_spanned(field, "only one field can be the key"));
+        }
+
+        key = Some(serialized_name(input, field)?);
+    }
+
+    let key = key
+        .ok_or_else(|| Error::new_spanned(&input.ident, "mark the key field with #[deta(key)]"))?;
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::deta::DetaItem for #ident #ty_generics #where_clause {
+            const KEY_FIELD: &'static str = #key;
+        }
+    })
+}
+
+/// Whether the field is marked with `#[deta(key)]`.
+fn is_key(field: &Field) -> Result<bool> {
+    let mut key = false;
+
+    for meta in nested_meta(&field.attrs, "deta")? {
+        match meta {
+            NestedMeta::Meta(Meta::Path(x)) if x.is_ident("key") => key = true,
+            x => return Err(Error::new_spanned(x, "unknown deta attribute")),
+        }
+    }
+
+    Ok(key)
+}
+
+/// The name of the field, as serialized by serde.
+fn serialized_name(input: &DeriveInput, field: &Field) -> Result<String> {
+    for meta in nested_meta(&field.attrs, "serde")? {
+        if let NestedMeta::Meta(Meta::NameValue(x)) = meta {
+            if let (true, Lit::Str(name)) = (x.path.is_ident("rename"), &x.lit) {
+                return Ok(name.value());
+            }
+        }
+    }
+
+    for meta in nested_meta(&input.attrs, "serde")? {
+        if let NestedMeta::Meta(x) = meta {
+            if x.path().is_ident("rename_all") {
+                let message = "rename the key field with #[serde(rename = \"...\")] too";
+                return Err(Error::new_spanned(&field.ident, message));
+            }
+        }
+    }
+
+    let ident = field
+        .ident
+        .as_ref()
+        .ok_or_else(|| Error::new_spanned(field, "DetaItem needs named fields"))?;
+    let name = ident.to_string();
+
+    Ok(name.trim_start_matches("r#").to_string())
+}
+
+/// The items of the attributes like `#[name(...)]`.
+fn nested_meta(attrs: &[Attribute], name: &str) -> Result<Vec<NestedMeta>> {
+    let mut nested = Vec::new();
+
+    for attr in attrs.iter().filter(|x| x.path.is_ident(name)) {
+        match attr.parse_meta()? {
+            Meta::List(x) => nested.extend(x.nested),
+            x => return Err(Error::new_spanned(x, format!("expected #[{}(...)]", name))),
+        }
+    }
+
+    Ok(nested)
+}
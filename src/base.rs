@@ -0,0 +1,1194 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use std::{fmt, mem, slice};
+
+use bytes::Bytes;
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use http::{header, Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::item::{self, DetaItem, IntoItem};
+use crate::{
+    retry, Deta, Error, ErrorResult, Item, PutManyReport, Query, RequestError, Result, TypedBase,
+    Update,
+};
+
+/// The maximum number of items in a single put request.
+const MAX_ITEMS: usize = 25;
+/// The maximum size of a single item.
+const MAX_ITEM_SIZE: usize = 400 * 1024;
+/// The maximum size of a single put request.
+const MAX_REQUEST_SIZE: usize = 16 * 1024 * 1024;
+/// The size of `{"items":[]}` wrapping the items of a put request.
+const REQUEST_OVERHEAD: usize = 12;
+
+/// A handle to a Deta Base.
+///
+/// Get one with [`Deta::base`](crate::Deta::base).
+/// It shares the `Project Key` and the connections of the `Deta` client,
+/// so `.clone()` it to reuse it.
+///
+/// # Examples
+///
+/// ```
+/// use deta::{Base, Deta, Item};
+/// # #[tokio::main]
+/// # async fn main() -> deta::Result<()> {
+/// let deta = Deta::new()?;
+///
+/// let base: Base = deta.base("main");
+/// base.put(Item::new_with_key("base_id", 60)).await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// The items can't be reached without naming a base first:
+///
+/// ```compile_fail
+/// use deta::Deta;
+/// # async fn get(deta: Deta) -> deta::Result<()> {
+/// let value: usize = deta.get("base_id").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Base {
+    deta: Deta,
+    name: Arc<String>,
+}
+
+impl Base {
+    pub(crate) fn new(deta: Deta, name: String) -> Self {
+        Self {
+            deta,
+            name: Arc::new(name),
+        }
+    }
+
+    /// The name of the base.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Binds the handle to a single type of items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::{Deta, TypedBase};
+    /// # fn main() -> deta::Result<()> {
+    /// let deta = Deta::new()?;
+    ///
+    /// let counts: TypedBase<usize> = deta.base("counts").typed();
+    /// let base = counts.into_base();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn typed<T>(self) -> TypedBase<T>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        TypedBase::new(self)
+    }
+
+    /// Get a stored item.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: The key (aka. ID) of the item you want to retrieve.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::InvalidItemKey`](crate::Error::InvalidItemKey)
+    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
+    /// * [`Error::ItemNotFound`](crate::Error::ItemNotFound)
+    /// * [`Error::Unauthorized`](crate::Error::Unauthorized)
+    /// * [`Error::Forbidden`](crate::Error::Forbidden)
+    /// * [`Error::TooManyRequests`](crate::Error::TooManyRequests)
+    /// * [`Error::ServerError`](crate::Error::ServerError)
+    /// * [`Error::JSONDeserializingFailed`](crate::Error::JSONDeserializingFailed)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::{Deta, Item};
+    /// # #[tokio::main]
+    /// # async fn main() -> deta::Result<()> {
+    /// let deta = Deta::new()?;
+    ///
+    /// let base = deta.base("main");
+    /// base.put(Item::new_with_key("get_id", 60)).await?;
+    /// let value: usize = base.get("get_id").await?;
+    ///
+    /// assert_eq!(value, 60);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get<T>(&self, key: impl fmt::Display) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let Item { value, .. } = self.get_item(key).await?;
+        Ok(value)
+    }
+
+    /// Get a stored item, or `None` if no item with `key` exists.
+    ///
+    /// Unlike [`get`](Base::get), a missing item isn't an error,
+    /// while every other failure, like an invalid `Project Key`, still is.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: The key (aka. ID) of the item you want to retrieve.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::InvalidItemKey`](crate::Error::InvalidItemKey)
+    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
+    /// * [`Error::Unauthorized`](crate::Error::Unauthorized)
+    /// * [`Error::Forbidden`](crate::Error::Forbidden)
+    /// * [`Error::TooManyRequests`](crate::Error::TooManyRequests)
+    /// * [`Error::ServerError`](crate::Error::ServerError)
+    /// * [`Error::JSONDeserializingFailed`](crate::Error::JSONDeserializingFailed)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::{Deta, Item};
+    /// # #[tokio::main]
+    /// # async fn main() -> deta::Result<()> {
+    /// let deta = Deta::new()?;
+    ///
+    /// let base = deta.base("main");
+    /// base.put(Item::new_with_key("get_opt_id", 60)).await?;
+    /// base.delete("get_opt_missing").await?;
+    ///
+    /// assert_eq!(base.get_opt::<usize>("get_opt_id").await?, Some(60));
+    /// assert_eq!(base.get_opt::<usize>("get_opt_missing").await?, None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_opt<T>(&self, key: impl fmt::Display) -> Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        match self.get(key).await {
+            Ok(x) => Ok(Some(x)),
+            Err(Error::ItemNotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get a stored item, along with its key and expiry.
+    ///
+    /// The value is read as described on [`Item`](crate::Item).
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: The key (aka. ID) of the item you want to retrieve.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::InvalidItemKey`](crate::Error::InvalidItemKey)
+    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
+    /// * [`Error::ItemNotFound`](crate::Error::ItemNotFound)
+    /// * [`Error::Unauthorized`](crate::Error::Unauthorized)
+    /// * [`Error::Forbidden`](crate::Error::Forbidden)
+    /// * [`Error::TooManyRequests`](crate::Error::TooManyRequests)
+    /// * [`Error::ServerError`](crate::Error::ServerError)
+    /// * [`Error::JSONDeserializingFailed`](crate::Error::JSONDeserializingFailed)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::{Deta, Item};
+    /// # #[tokio::main]
+    /// # async fn main() -> deta::Result<()> {
+    /// let deta = Deta::new()?;
+    ///
+    /// let base = deta.base("main");
+    /// base.put(Item::new_with_key("get_item_id", 60)).await?;
+    /// let item: Item<usize> = base.get_item("get_item_id").await?;
+    ///
+    /// assert_eq!(item, Item::new_with_key("get_item_id", 60));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_item<T>(&self, key: impl fmt::Display) -> Result<Item<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let endpoint = Endpoint::item(key)?;
+        let value = self
+            .send(Method::GET, &endpoint, None, Error::from_status)
+            .await?;
+
+        Item::from_value(value)
+    }
+
+    /// Get a stored struct, with its key field set to the key of the item.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: The key (aka. ID) of the item you want to retrieve.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::InvalidItemKey`](crate::Error::InvalidItemKey)
+    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
+    /// * [`Error::ItemNotFound`](crate::Error::ItemNotFound)
+    /// * [`Error::Unauthorized`](crate::Error::Unauthorized)
+    /// * [`Error::Forbidden`](crate::Error::Forbidden)
+    /// * [`Error::TooManyRequests`](crate::Error::TooManyRequests)
+    /// * [`Error::ServerError`](crate::Error::ServerError)
+    /// * [`Error::JSONDeserializingFailed`](crate::Error::JSONDeserializingFailed)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::{Deta, DetaItem};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// struct User {
+    ///     id: String,
+    ///     name: String,
+    /// }
+    ///
+    /// impl DetaItem for User {
+    ///     const KEY_FIELD: &'static str = "id";
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> deta::Result<()> {
+    /// let deta = Deta::new()?;
+    ///
+    /// let base = deta.base("users");
+    /// let user = User {
+    ///     id: "jimmy".to_string(),
+    ///     name: "Jimmy".to_string(),
+    /// };
+    /// base.put(user).await?;
+    ///
+    /// let user: User = base.get_keyed("jimmy").await?;
+    /// assert_eq!(user.id, "jimmy");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_keyed<T>(&self, key: impl fmt::Display) -> Result<T>
+    where
+        T: DetaItem,
+    {
+        let endpoint = Endpoint::item(key)?;
+        let value = self
+            .send(Method::GET, &endpoint, None, Error::from_status)
+            .await?;
+
+        item::from_keyed_value(value)
+    }
+
+    /// Fetch all the stored items matching a query.
+    ///
+    /// This follows the pagination of the results until every matching item is fetched.
+    ///
+    /// # Arguments
+    ///
+    /// * `query`: A `Query` struct.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
+    /// * [`Error::BadRequest`](crate::Error::BadRequest)
+    /// * [`Error::Unauthorized`](crate::Error::Unauthorized)
+    /// * [`Error::Forbidden`](crate::Error::Forbidden)
+    /// * [`Error::TooManyRequests`](crate::Error::TooManyRequests)
+    /// * [`Error::ServerError`](crate::Error::ServerError)
+    /// * [`Error::JSONDeserializingFailed`](crate::Error::JSONDeserializingFailed)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::{Deta, Item, Query};
+    /// # #[tokio::main]
+    /// # async fn main() -> deta::Result<()> {
+    /// let deta = Deta::new()?;
+    ///
+    /// let base = deta.base("main");
+    /// base.put(Item::new_with_key("query_id", 60)).await?;
+    /// let items: Vec<Item<usize>> = base
+    ///     .query(Query::new().equal("value", 60).prefix("key", "query_"))
+    ///     .await?;
+    ///
+    /// assert!(items.contains(&Item::new_with_key("query_id", 60)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn query<T>(&self, query: Query) -> Result<Vec<Item<T>>>
+    where
+        T: DeserializeOwned,
+    {
+        self.query_stream(query).try_collect().await
+    }
+
+    /// Fetch all the stored structs matching a query, with their key field set to the key of the item.
+    ///
+    /// # Arguments
+    ///
+    /// * `query`: A `Query` struct.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
+    /// * [`Error::BadRequest`](crate::Error::BadRequest)
+    /// * [`Error::Unauthorized`](crate::Error::Unauthorized)
+    /// * [`Error::Forbidden`](crate::Error::Forbidden)
+    /// * [`Error::TooManyRequests`](crate::Error::TooManyRequests)
+    /// * [`Error::ServerError`](crate::Error::ServerError)
+    /// * [`Error::JSONDeserializingFailed`](crate::Error::JSONDeserializingFailed)
+    pub async fn query_keyed<T>(&self, query: Query) -> Result<Vec<T>>
+    where
+        T: DetaItem,
+    {
+        self.query_values(query)
+            .map(|x| x.and_then(item::from_keyed_value))
+            .try_collect()
+            .await
+    }
+
+    /// Stream all the stored items matching a query.
+    ///
+    /// The items are fetched a page at a time, following the pagination of the results,
+    /// so only a single page is held in memory.
+    /// The size of a page can be set with [`Query::limit`](crate::Query::limit).
+    ///
+    /// The stream ends after the first error.
+    ///
+    /// # Arguments
+    ///
+    /// * `query`: A `Query` struct.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
+    /// * [`Error::BadRequest`](crate::Error::BadRequest)
+    /// * [`Error::Unauthorized`](crate::Error::Unauthorized)
+    /// * [`Error::Forbidden`](crate::Error::Forbidden)
+    /// * [`Error::TooManyRequests`](crate::Error::TooManyRequests)
+    /// * [`Error::ServerError`](crate::Error::ServerError)
+    /// * [`Error::JSONDeserializingFailed`](crate::Error::JSONDeserializingFailed)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::{Deta, Item, Query};
+    /// use futures::TryStreamExt;
+    /// # #[tokio::main]
+    /// # async fn main() -> deta::Result<()> {
+    /// let deta = Deta::new()?;
+    ///
+    /// let base = deta.base("numbers");
+    /// base.put(Item::new_with_key("query_stream_id", 60)).await?;
+    ///
+    /// let mut stream = Box::pin(base.query_stream::<usize>(Query::new().limit(10)));
+    /// while let Some(item) = stream.try_next().await? {
+    ///     println!("{:?}: {}", item.key, item.value);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn query_stream<T>(&self, query: Query) -> impl Stream<Item = Result<Item<T>>>
+    where
+        T: DeserializeOwned,
+    {
+        self.query_values(query)
+            .map(|x| x.and_then(Item::from_value))
+    }
+
+    /// Streams the raw items matching a query, following the pagination of the results.
+    fn query_values(&self, query: Query) -> impl Stream<Item = Result<serde_json::Value>> {
+        let state = (self.clone(), query, Some(None));
+
+        stream::try_unfold(state, |(base, query, last)| async move {
+            let last = match last {
+                Some(x) => x,
+                None => return Ok(None),
+            };
+
+            let QueryResult { paging, items } = base.query_page(&query, last).await?;
+            let items = stream::iter(items.into_iter().map(Ok));
+
+            Ok(Some((items, (base, query, paging.last.map(Some)))))
+        })
+        .try_flatten()
+    }
+
+    async fn query_page(&self, query: &Query, last: Option<String>) -> Result<QueryResult> {
+        let mut req_body = serde_json::json!({ "query": query });
+
+        if let Some(x) = query.page_limit() {
+            req_body["limit"] = serde_json::json!(x);
+        }
+
+        if let Some(x) = last {
+            req_body["last"] = serde_json::json!(x);
+        }
+
+        let value = self
+            .send(
+                Method::POST,
+                &Endpoint::Query,
+                Some(req_body),
+                Error::from_status,
+            )
+            .await?;
+
+        serde_json::from_value(value).map_err(Error::JSONDeserializingFailed)
+    }
+
+    /// Delete a stored item.
+    ///
+    /// Deleting an item which doesn't exist succeeds too.
+    /// Use [`delete_existing`](Base::delete_existing) to know whether it existed.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: The key (aka. ID) of the item you want to delete.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::InvalidItemKey`](crate::Error::InvalidItemKey)
+    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
+    /// * [`Error::Unauthorized`](crate::Error::Unauthorized)
+    /// * [`Error::Forbidden`](crate::Error::Forbidden)
+    /// * [`Error::TooManyRequests`](crate::Error::TooManyRequests)
+    /// * [`Error::ServerError`](crate::Error::ServerError)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::{Deta, Item, Result};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// let deta = Deta::new()?;
+    ///
+    /// let base = deta.base("main");
+    /// base.put(Item::new_with_key("delete_id", 60)).await?;
+    /// base.delete("delete_id").await?;
+    ///
+    /// let item: Result<usize> = base.get("delete_id").await;
+    /// assert!(item.is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete(&self, key: impl fmt::Display) -> Result<()> {
+        let endpoint = Endpoint::item(key)?;
+        self.send(Method::DELETE, &endpoint, None, Error::from_status)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Delete a stored item, if it exists.
+    ///
+    /// Returns whether the item existed.
+    /// The item is fetched first, so an item stored in between both requests is deleted,
+    /// but reported as missing.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: The key (aka. ID) of the item you want to delete.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::InvalidItemKey`](crate::Error::InvalidItemKey)
+    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
+    /// * [`Error::Unauthorized`](crate::Error::Unauthorized)
+    /// * [`Error::Forbidden`](crate::Error::Forbidden)
+    /// * [`Error::TooManyRequests`](crate::Error::TooManyRequests)
+    /// * [`Error::ServerError`](crate::Error::ServerError)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::{Deta, Item};
+    /// # #[tokio::main]
+    /// # async fn main() -> deta::Result<()> {
+    /// let deta = Deta::new()?;
+    ///
+    /// let base = deta.base("main");
+    /// base.put(Item::new_with_key("delete_existing_id", 60)).await?;
+    ///
+    /// assert!(base.delete_existing("delete_existing_id").await?);
+    /// assert!(!base.delete_existing("delete_existing_id").await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete_existing(&self, key: impl fmt::Display) -> Result<bool> {
+        let endpoint = Endpoint::item(key)?;
+        let exists = match self
+            .send(Method::GET, &endpoint, None, Error::from_status)
+            .await
+        {
+            Ok(_) => true,
+            Err(Error::ItemNotFound(_)) => false,
+            Err(e) => return Err(e),
+        };
+
+        if exists {
+            self.send(Method::DELETE, &endpoint, None, Error::from_status)
+                .await?;
+        }
+
+        Ok(exists)
+    }
+
+    /// Stores an item.
+    /// This request overwrites an item if the key already exists.
+    ///
+    /// Returns the key, if successful.
+    ///
+    /// # Arguments
+    ///
+    /// * `item`: An `Item`, or a struct implementing [`DetaItem`](crate::DetaItem).
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::JSONSerializingFailed`](crate::Error::JSONSerializingFailed)
+    /// * [`Error::InvalidItemKey`](crate::Error::InvalidItemKey)
+    /// * [`Error::ItemTooLarge`](crate::Error::ItemTooLarge)
+    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
+    /// * [`Error::BadRequest`](crate::Error::BadRequest)
+    /// * [`Error::Unauthorized`](crate::Error::Unauthorized)
+    /// * [`Error::Forbidden`](crate::Error::Forbidden)
+    /// * [`Error::TooManyRequests`](crate::Error::TooManyRequests)
+    /// * [`Error::ServerError`](crate::Error::ServerError)
+    /// * [`Error::ItemRejected`](crate::Error::ItemRejected)
+    /// * [`Error::JSONDeserializingFailed`](crate::Error::JSONDeserializingFailed)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::{Deta, Item};
+    /// # #[tokio::main]
+    /// # async fn main() -> deta::Result<()> {
+    /// let deta = Deta::new()?;
+    ///
+    /// let base = deta.base("main");
+    /// # base.delete(5);
+    /// let item = Item::new_with_key("put_id", 5);
+    /// assert_eq!(base.put(item).await?, "put_id");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn put<I>(&self, item: I) -> Result<String>
+    where
+        I: IntoItem,
+    {
+        let (value, _) = prepare(slice::from_ref(&item))?.remove(0);
+        let req_body = serde_json::json!({ "items": [value] });
+
+        let value = self
+            .send(
+                Method::PUT,
+                &Endpoint::Items,
+                Some(req_body),
+                Error::from_status,
+            )
+            .await?;
+
+        let PutResult {
+            processed, failed, ..
+        } = serde_json::from_value(value).map_err(Error::JSONDeserializingFailed)?;
+        let first = |x: Option<Put>| x.and_then(|x| x.items.into_iter().next());
+
+        match (first(processed), first(failed)) {
+            (Some(mut x), _) => match x["key"].take() {
+                serde_json::Value::String(x) => Ok(x),
+                _ => Err(Error::deserializing("the stored key isn't a string")),
+            },
+            (None, Some(x)) => Err(Error::ItemRejected(x)),
+            (None, None) => Err(Error::deserializing("no item in the response")),
+        }
+    }
+
+    /// Stores multiple items in a single request.
+    /// This request overwrites an item if the key already exists.
+    ///
+    /// It returns a [`PutManyReport`](crate::PutManyReport) with the keys of the stored items,
+    /// and the items which weren't stored.
+    /// The items which weren't stored are sent again as allowed by the [`RetryPolicy`](crate::RetryPolicy),
+    /// if [`retry_failed_items`](crate::RetryPolicy::retry_failed_items) is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `items`: A `Vec` of `Item`s, or of structs implementing [`DetaItem`](crate::DetaItem).
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::JSONSerializingFailed`](crate::Error::JSONSerializingFailed)
+    /// * [`Error::InvalidItemKey`](crate::Error::InvalidItemKey)
+    /// * [`Error::VecTooLong`](crate::Error::VecTooLong)
+    /// * [`Error::ItemTooLarge`](crate::Error::ItemTooLarge)
+    /// * [`Error::DuplicateKey`](crate::Error::DuplicateKey)
+    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
+    /// * [`Error::BadRequest`](crate::Error::BadRequest)
+    /// * [`Error::Unauthorized`](crate::Error::Unauthorized)
+    /// * [`Error::Forbidden`](crate::Error::Forbidden)
+    /// * [`Error::TooManyRequests`](crate::Error::TooManyRequests)
+    /// * [`Error::ServerError`](crate::Error::ServerError)
+    /// * [`Error::JSONDeserializingFailed`](crate::Error::JSONDeserializingFailed)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::{Deta, Item};
+    /// # #[tokio::main]
+    /// # async fn main() -> deta::Result<()> {
+    /// let deta = Deta::new()?;
+    ///
+    /// let base = deta.base("main");
+    /// let numbers = ["zero", "one", "two", "three", "four"];
+    /// let vec = numbers
+    ///     .iter()
+    ///     .enumerate()
+    ///     .map(|(c, x)| Item::new_with_key(c, x))
+    ///     .collect::<Vec<_>>();
+    /// let report = base.put_many(vec).await?;
+    ///
+    /// assert_eq!(report.processed().len() + report.failed().len(), 5);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn put_many<I>(&self, items: Vec<I>) -> Result<PutManyReport<I>>
+    where
+        I: IntoItem,
+    {
+        if items.len() > MAX_ITEMS {
+            return Err(Error::VecTooLong);
+        }
+
+        let values = prepare(&items)?;
+        let batch = items.into_iter().zip(values).map(|(x, (y, _))| (x, y));
+
+        self.put_batch(batch.collect()).await.into_result()
+    }
+
+    /// Stores any number of items.
+    /// This request overwrites an item if the key already exists.
+    ///
+    /// The items are split into batches of at most 25 items and 16 MB,
+    /// and up to `concurrency` batches are sent at a time.
+    ///
+    /// It returns the reports of all the batches, merged like with [`put_many`](Base::put_many).
+    /// Every item is checked before sending the first batch,
+    /// so a duplicate key fails even if the items would be in different batches.
+    /// If a batch can't be stored, the other batches are still sent,
+    /// its items are in [`failed`](crate::PutManyReport::failed),
+    /// and its error is in [`errors`](crate::PutManyReport::errors).
+    ///
+    /// # Arguments
+    ///
+    /// * `items`: A `Vec` of `Item`s, or of structs implementing [`DetaItem`](crate::DetaItem).
+    /// * `concurrency`: The maximum number of requests sent at a time.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::JSONSerializingFailed`](crate::Error::JSONSerializingFailed)
+    /// * [`Error::InvalidItemKey`](crate::Error::InvalidItemKey)
+    /// * [`Error::ItemTooLarge`](crate::Error::ItemTooLarge)
+    /// * [`Error::DuplicateKey`](crate::Error::DuplicateKey)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::{Deta, Item};
+    /// # #[tokio::main]
+    /// # async fn main() -> deta::Result<()> {
+    /// let deta = Deta::new()?;
+    ///
+    /// let base = deta.base("main");
+    /// let vec = (0..100)
+    ///     .map(|x| Item::new_with_key(format!("put_all_{}", x), x))
+    ///     .collect::<Vec<_>>();
+    /// let report = base.put_all(vec, 4).await?;
+    ///
+    /// assert_eq!(report.processed().len() + report.failed().len(), 100);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn put_all<I>(&self, items: Vec<I>, concurrency: usize) -> Result<PutManyReport<I>>
+    where
+        I: IntoItem,
+    {
+        let mut batches = Vec::new();
+        let mut batch = Vec::new();
+        let mut size = REQUEST_OVERHEAD;
+
+        let values = prepare(&items)?;
+        for (item, (value, len)) in items.into_iter().zip(values) {
+            if !batch.is_empty() && (batch.len() == MAX_ITEMS || size + len > MAX_REQUEST_SIZE) {
+                batches.push(mem::take(&mut batch));
+                size = REQUEST_OVERHEAD;
+            }
+
+            size += len;
+            batch.push((item, value));
+        }
+
+        if !batch.is_empty() {
+            batches.push(batch);
+        }
+
+        let report = stream::iter(batches)
+            .map(|x| self.put_batch(x))
+            .buffer_unordered(concurrency.max(1))
+            .fold(PutManyReport::new(), |mut report, x| async move {
+                report.merge(x);
+                report
+            })
+            .await;
+
+        Ok(report)
+    }
+
+    /// Stores a batch of items.
+    ///
+    /// If it fails, the items which weren't reported as stored are failed along with the error.
+    async fn put_batch<I>(&self, mut batch: Vec<(I, serde_json::Value)>) -> PutManyReport<I> {
+        let mut report = PutManyReport::new();
+
+        if let Err(e) = self.put_attempts(&mut batch, &mut report).await {
+            for (item, _) in batch {
+                report.push_failed(item, None);
+            }
+            report.push_error(e);
+        }
+
+        report
+    }
+
+    /// Sends the attempts to store a batch, pairing the failed items with the items they were made of.
+    ///
+    /// Failed items are sent again if the [`RetryPolicy`] allows it.
+    /// The items left in `batch` on an error weren't stored.
+    async fn put_attempts<I>(
+        &self,
+        batch: &mut Vec<(I, serde_json::Value)>,
+        report: &mut PutManyReport<I>,
+    ) -> Result<()> {
+        let mut attempt = 1;
+
+        loop {
+            let items = batch.iter().map(|(_, x)| x).collect::<Vec<_>>();
+            let req_body = serde_json::json!({ "items": items });
+
+            let value = self
+                .send(
+                    Method::PUT,
+                    &Endpoint::Items,
+                    Some(req_body),
+                    Error::from_status,
+                )
+                .await?;
+            let PutResult {
+                processed,
+                failed,
+                errors,
+            } = serde_json::from_value(value).map_err(Error::JSONDeserializingFailed)?;
+
+            let keys = processed
+                .map(|x| x.items)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|mut x| match x["key"].take() {
+                    serde_json::Value::String(x) => Ok(x),
+                    _ => Err(Error::deserializing("a stored key isn't a string")),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            for x in keys {
+                report.push_processed(x);
+            }
+
+            let mut failed_batch = Vec::new();
+            for x in failed.map(|x| x.items).unwrap_or_default() {
+                match batch.iter().position(|(_, y)| is_echo(y, &x)) {
+                    Some(index) => failed_batch.push(batch.swap_remove(index)),
+                    None => report.push_unmatched(x),
+                }
+            }
+            *batch = failed_batch;
+
+            let retry = &self.deta.retry;
+            let delay = if batch.is_empty() || !retry.retries_failed_items() {
+                None
+            } else {
+                retry.delay(attempt, true, None)
+            };
+
+            match delay {
+                Some(x) => self.deta.transport.delay(x).await,
+                None => {
+                    let reason = Some(errors.join(", ")).filter(|x| !x.is_empty());
+                    for (item, _) in batch.drain(..) {
+                        report.push_failed(item, reason.clone());
+                    }
+                    return Ok(());
+                }
+            }
+
+            attempt += 1;
+        }
+    }
+
+    /// Creates a new item only if no item with the same `key` exists.
+    ///
+    /// Returns the key, if successful. If the same key exists returns an Error.
+    ///
+    /// # Arguments
+    ///
+    /// * `item`: An `Item`, or a struct implementing [`DetaItem`](crate::DetaItem).
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::JSONSerializingFailed`](crate::Error::JSONSerializingFailed)
+    /// * [`Error::InvalidItemKey`](crate::Error::InvalidItemKey)
+    /// * [`Error::ItemTooLarge`](crate::Error::ItemTooLarge)
+    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
+    /// * [`Error::KeyConflict`](crate::Error::KeyConflict)
+    /// * [`Error::BadRequest`](crate::Error::BadRequest)
+    /// * [`Error::Unauthorized`](crate::Error::Unauthorized)
+    /// * [`Error::Forbidden`](crate::Error::Forbidden)
+    /// * [`Error::TooManyRequests`](crate::Error::TooManyRequests)
+    /// * [`Error::ServerError`](crate::Error::ServerError)
+    /// * [`Error::JSONDeserializingFailed`](crate::Error::JSONDeserializingFailed)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::{Deta, Item};
+    /// # #[tokio::main]
+    /// # async fn main() -> deta::Result<()> {
+    /// let deta = Deta::new()?;
+    ///
+    /// let base = deta.base("main");
+    /// # base.delete("insert_id").await?;
+    /// let item = Item::new_with_key("insert_id", 60);
+    /// base.insert(item).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn insert<I>(&self, item: I) -> Result<String>
+    where
+        I: IntoItem,
+    {
+        let (value, _) = prepare(slice::from_ref(&item))?.remove(0);
+        let req_body = serde_json::json!({ "item": value });
+
+        let json = self
+            .send(
+                Method::POST,
+                &Endpoint::Items,
+                Some(req_body),
+                Error::from_status,
+            )
+            .await?;
+
+        Ok(json["key"]
+            .as_str()
+            .ok_or_else(|| Error::deserializing("the key of the item isn't a string"))?
+            .to_string())
+    }
+
+    /// Updates an item only if an item with `key` exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `update`: An `Update` struct.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::InvalidItemKey`](crate::Error::InvalidItemKey)
+    /// * [`Error::JSONSerializingFailed`](crate::Error::JSONSerializingFailed)
+    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
+    /// * [`Error::KeyNonexistent`](crate::Error::KeyNonexistent)
+    /// * [`Error::BadRequest`](crate::Error::BadRequest)
+    /// * [`Error::Unauthorized`](crate::Error::Unauthorized)
+    /// * [`Error::Forbidden`](crate::Error::Forbidden)
+    /// * [`Error::TooManyRequests`](crate::Error::TooManyRequests)
+    /// * [`Error::ServerError`](crate::Error::ServerError)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use deta::{Deta, Update};
+    /// # #[tokio::main]
+    /// # async fn main() -> deta::Result<()> {
+    /// let deta = Deta::new()?;
+    ///
+    /// let base = deta.base("main");
+    /// base.update(
+    ///     "user-a",
+    ///     Update::new()
+    ///         .set("profile.age", 33)
+    ///         .set("profile.active", true)
+    ///         .set("profile.email", "jimmy@deta.sh")
+    ///         .increment("purchases", 2)
+    ///         .append("likes", "ramen")
+    ///         .prepend("likes", "noodles")
+    ///         .delete("profile.hometown")
+    ///         .delete("on_mobile"),
+    /// )
+    /// .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn update(&self, key: impl fmt::Display, update: Update) -> Result<()> {
+        update.check()?;
+        let endpoint = Endpoint::item(key)?;
+        let req_body = serde_json::to_value(&update).map_err(Error::JSONSerializingFailed)?;
+
+        self.send(Method::PATCH, &endpoint, Some(req_body), |e| {
+            if e.status() == Some(StatusCode::NOT_FOUND) {
+                Error::KeyNonexistent(e)
+            } else {
+                Error::from_status(e)
+            }
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    /// Sends a request to the base and deserializes the response.
+    ///
+    /// Responses with a status code other than a success are turned into an error by `error`.
+    async fn send(
+        &self,
+        method: Method,
+        endpoint: &Endpoint,
+        body: Option<serde_json::Value>,
+        error: impl FnOnce(RequestError) -> Error,
+    ) -> Result<serde_json::Value> {
+        let Response {
+            status,
+            body,
+            retry_after,
+            request,
+        } = self.execute(method, endpoint, body).await?;
+
+        if !status.is_success() {
+            let messages = serde_json::from_slice::<ErrorResult>(&body)
+                .map(|x| x.errors)
+                .unwrap_or_default();
+            let request = request
+                .with_status(status, messages)
+                .with_retry_after(retry_after);
+            return Err(error(request));
+        }
+
+        serde_json::from_slice(&body).map_err(Error::JSONDeserializingFailed)
+    }
+
+    /// Sends a request to the base, without checking the status code of the response.
+    ///
+    /// Failed requests are sent again as allowed by the [`RetryPolicy`].
+    async fn execute(
+        &self,
+        method: Method,
+        endpoint: &Endpoint,
+        body: Option<serde_json::Value>,
+    ) -> Result<Response> {
+        // Inserts and updates may be applied twice if they're sent again.
+        let idempotent = !matches!(
+            (&method, endpoint),
+            (&Method::POST, Endpoint::Items) | (&Method::PATCH, _)
+        );
+        let mut attempt = 1;
+
+        loop {
+            let (retry_after, result) = match self.attempt(&method, endpoint, body.clone()).await {
+                Ok(x) if retry::is_retryable(x.status) => (x.retry_after, Ok(x)),
+                Err(e) if e.is_retryable() => (None, Err(e)),
+                result => return result,
+            };
+
+            match self.deta.retry.delay(attempt, idempotent, retry_after) {
+                Some(x) => self.deta.transport.delay(x).await,
+                None => return result,
+            }
+
+            attempt += 1;
+        }
+    }
+
+    /// Sends a request to the base once.
+    async fn attempt(
+        &self,
+        method: &Method,
+        endpoint: &Endpoint,
+        body: Option<serde_json::Value>,
+    ) -> Result<Response> {
+        let method = method.clone();
+        let name = crate::encode(&self.name);
+        let url = format!("{}/{}/{}", self.deta.url, name, endpoint.path());
+        let request = RequestError::new(
+            method.clone(),
+            format!("{}/{}/{}", self.deta.url, name, endpoint.redacted_path()),
+        );
+
+        let mut builder = http::Request::builder()
+            .method(method)
+            .uri(url)
+            .header("x-api-key", self.deta.key.clone());
+
+        let body = match body {
+            Some(x) => {
+                builder = builder.header(header::CONTENT_TYPE, "application/json");
+                serde_json::to_vec(&x).map_err(Error::JSONSerializingFailed)?
+            }
+            None => Vec::new(),
+        };
+
+        let response = match builder.body(Bytes::from(body)) {
+            Ok(x) => self.deta.transport.send(x).await,
+            Err(e) => Err(e.into()),
+        };
+        let response = match response {
+            Ok(x) => x,
+            Err(e) => return Err(Error::RequestSendError(request.with_source(e))),
+        };
+
+        let status = response.status();
+        let retry_after = retry::retry_after(response.headers());
+        let body = match response.into_body().bytes().await {
+            Ok(x) => x,
+            Err(e) => return Err(Error::RequestSendError(request.with_source(e))),
+        };
+
+        Ok(Response {
+            status,
+            body,
+            retry_after,
+            request,
+        })
+    }
+}
+
+/// Whether `echo`, reported by the Deta Base, is the item `sent`.
+///
+/// Items sent without a key are echoed with the key generated for them.
+fn is_echo(sent: &serde_json::Value, echo: &serde_json::Value) -> bool {
+    match sent.get("key") {
+        Some(x) => echo.get("key") == Some(x),
+        None => echo
+            .as_object()
+            .zip(sent.as_object())
+            .is_some_and(|(echo, sent)| {
+                echo.len() == sent.len() + 1 && sent.iter().all(|(key, x)| echo.get(key) == Some(x))
+            }),
+    }
+}
+
+/// Makes Deta Base items out of items to be stored together,
+/// along with their size serialized to JSON, including a separating comma.
+///
+/// Fails if an item is too large, or if several items have the same key.
+fn prepare<I>(items: &[I]) -> Result<Vec<(serde_json::Value, usize)>>
+where
+    I: IntoItem,
+{
+    let mut keys = HashSet::new();
+
+    items
+        .iter()
+        .map(|item| {
+            let value = item.to_value()?;
+            let key = value.get("key").and_then(serde_json::Value::as_str);
+            let size = serde_json::to_vec(&value)
+                .map_err(Error::JSONSerializingFailed)?
+                .len();
+
+            if size > MAX_ITEM_SIZE {
+                let key = key.map(str::to_string);
+                return Err(Error::ItemTooLarge { key, size });
+            }
+
+            if let Some(x) = key {
+                if !keys.insert(x.to_string()) {
+                    return Err(Error::DuplicateKey(x.to_string()));
+                }
+            }
+
+            Ok((value, size + 1))
+        })
+        .collect()
+}
+
+/// An endpoint of a Deta Base.
+enum Endpoint {
+    Items,
+    Item(String),
+    Query,
+}
+
+impl Endpoint {
+    /// The endpoint of the item with `key`, if the key is valid.
+    fn item(key: impl fmt::Display) -> Result<Self> {
+        let key = key.to_string();
+        item::validate_key(&key)?;
+
+        Ok(Self::Item(key))
+    }
+
+    fn path(&self) -> String {
+        match self {
+            Self::Items => "items".to_string(),
+            Self::Item(x) => format!("items/{}", crate::encode(x)),
+            Self::Query => "query".to_string(),
+        }
+    }
+
+    /// The path without the key of the item, to be shown in errors.
+    fn redacted_path(&self) -> &'static str {
+        match self {
+            Self::Items => "items",
+            Self::Item(_) => "items/{key}",
+            Self::Query => "query",
+        }
+    }
+}
+
+/// A response from a Deta Base.
+struct Response {
+    status: StatusCode,
+    body: Bytes,
+    retry_after: Option<Duration>,
+    request: RequestError,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Put {
+    items: Vec<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PutResult {
+    processed: Option<Put>,
+    failed: Option<Put>,
+    #[serde(default)]
+    errors: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Paging {
+    size: usize,
+    last: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct QueryResult {
+    paging: Paging,
+    items: Vec<serde_json::Value>,
+}
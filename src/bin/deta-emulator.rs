@@ -0,0 +1,204 @@
+//! A local stand-in for the Deta Base HTTP API.
+//!
+//! ```text
+//! deta-emulator [--host <host>] [--port <port>] [--data <file>]
+//! ```
+//!
+//! Serves `/v1/{project_id}/{base_name}/items` and `/v1/{project_id}/{base_name}/query`.
+//! The items are kept in memory, and written to the `--data` file after every change if given.
+//!
+//! Point a client at it with the `DETA_BASE_ENDPOINT` environment variable,
+//! or with [`DetaBuilder::endpoint`](deta::DetaBuilder::endpoint),
+//! set to `http://localhost:4000/v1`.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::{env, fs, process};
+
+use deta::testing::MemoryBackend;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{header, Body, Method, Request, Response, Server, StatusCode};
+use serde_json::{json, Value};
+
+const USAGE: &str = "usage: deta-emulator [--host <host>] [--port <port>] [--data <file>]";
+
+struct Options {
+    address: SocketAddr,
+    data: Option<PathBuf>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut host = "127.0.0.1".to_string();
+        let mut port = "4000".to_string();
+        let mut data = None;
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+
+            match arg.as_str() {
+                "--host" => host = value()?,
+                "--port" => port = value()?,
+                "--data" => data = Some(PathBuf::from(value()?)),
+                "--help" | "-h" => return Err(USAGE.to_string()),
+                _ => return Err(format!("unknown argument {}\n{}", arg, USAGE)),
+            }
+        }
+
+        let address = format!("{}:{}", host, port)
+            .parse()
+            .map_err(|_| format!("invalid address {}:{}", host, port))?;
+
+        Ok(Self { address, data })
+    }
+}
+
+/// The backends of every project, and where to save them.
+struct Emulator {
+    projects: Mutex<HashMap<String, MemoryBackend>>,
+    data: Option<PathBuf>,
+}
+
+impl Emulator {
+    fn new(data: Option<PathBuf>) -> Result<Self, String> {
+        let mut projects = HashMap::new();
+
+        if let Some(path) = data.as_ref().filter(|x| x.exists()) {
+            let file = fs::read(path).map_err(|e| format!("can't read {:?}: {}", path, e))?;
+            let saved: HashMap<String, Value> = serde_json::from_slice(&file)
+                .map_err(|e| format!("can't parse {:?}: {}", path, e))?;
+
+            for (project, items) in saved {
+                let backend = MemoryBackend::import(items)
+                    .map_err(|e| format!("can't parse {:?}: {}", path, e))?;
+                projects.insert(project, backend);
+            }
+        }
+
+        Ok(Self {
+            projects: Mutex::new(projects),
+            data,
+        })
+    }
+
+    fn backend(&self, project: &str) -> MemoryBackend {
+        let mut projects = self.projects.lock().unwrap_or_else(|e| e.into_inner());
+        projects.entry(project.to_string()).or_default().clone()
+    }
+
+    /// Writes every project to the data file, if there is one.
+    fn save(&self) -> Result<(), String> {
+        let path = match &self.data {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+
+        let projects = self.projects.lock().unwrap_or_else(|e| e.into_inner());
+        let saved = projects
+            .iter()
+            .map(|(project, backend)| (project.clone(), backend.export()))
+            .collect::<HashMap<_, _>>();
+        let file = serde_json::to_vec_pretty(&saved).map_err(|e| e.to_string())?;
+
+        let temp = path.with_extension("tmp");
+        fs::write(&temp, file)
+            .and_then(|_| fs::rename(&temp, path))
+            .map_err(|e| format!("can't write {:?}: {}", path, e))
+    }
+
+    async fn handle(&self, request: Request<Body>) -> (StatusCode, Value) {
+        let (parts, body) = request.into_parts();
+
+        let path = match parts.uri.path().strip_prefix("/v1/") {
+            Some(x) => x.to_string(),
+            None => return error(StatusCode::NOT_FOUND, "Not found"),
+        };
+        let (project, path) = match path.find('/') {
+            Some(i) => (&path[..i], &path[i + 1..]),
+            None => return error(StatusCode::NOT_FOUND, "Not found"),
+        };
+
+        if !parts.headers.contains_key("x-api-key") {
+            return error(StatusCode::UNAUTHORIZED, "Unauthorized");
+        }
+
+        let body = match hyper::body::to_bytes(body).await {
+            Ok(x) => x,
+            Err(_) => return error(StatusCode::BAD_REQUEST, "Bad request"),
+        };
+        let body = if body.is_empty() {
+            None
+        } else {
+            match serde_json::from_slice(&body) {
+                Ok(x) => Some(x),
+                Err(_) => return error(StatusCode::BAD_REQUEST, "Invalid JSON"),
+            }
+        };
+
+        let (status, value) = self.backend(project).handle(&parts.method, path, body);
+
+        let changed = !(parts.method == Method::GET || path.ends_with("/query"));
+        if changed && status.is_success() {
+            if let Err(e) = self.save() {
+                eprintln!("{}", e);
+                return error(StatusCode::INTERNAL_SERVER_ERROR, "Can't save the items");
+            }
+        }
+
+        (status, value)
+    }
+}
+
+fn error(status: StatusCode, message: &str) -> (StatusCode, Value) {
+    (status, json!({ "errors": [message] }))
+}
+
+#[tokio::main]
+async fn main() {
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    };
+
+    let emulator = match Emulator::new(options.data) {
+        Ok(x) => Arc::new(x),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+
+    let service = make_service_fn(move |_| {
+        let emulator = emulator.clone();
+
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let emulator = emulator.clone();
+
+                async move {
+                    let (status, value) = emulator.handle(request).await;
+                    let response = Response::builder()
+                        .status(status)
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(Body::from(value.to_string()));
+
+                    Ok::<_, Infallible>(response.unwrap_or_default())
+                }
+            }))
+        }
+    });
+
+    let server = Server::bind(&options.address).serve(service);
+    eprintln!("listening on http://{}/v1", options.address);
+
+    if let Err(e) = server.await {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
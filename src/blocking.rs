@@ -0,0 +1,285 @@
+//! A client which sends the requests synchronously.
+//!
+//! Only available with the `blocking` feature.
+//! The methods block the current thread until the response is received,
+//! so they mustn't be called from within an async runtime.
+//!
+//! # Examples
+//!
+//! ```
+//! use deta::blocking::Deta;
+//! use deta::Item;
+//! # fn main() -> deta::Result<()> {
+//! let deta = Deta::new()?;
+//!
+//! let base = deta.base("main");
+//! base.put(Item::new_with_key("blocking_id", 60))?;
+//! let value: usize = base.get("blocking_id")?;
+//!
+//! assert_eq!(value, 60);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use tokio::runtime::{self, Runtime};
+
+use crate::{Error, IntoItem, Item, PutManyReport, Query, Result, RetryPolicy, Update};
+
+/// The blocking `Deta` client.
+///
+/// It wraps an async [`Deta`](crate::Deta) client, and a runtime which drives its requests.
+/// Like the async client, it uses an `Arc` internally, so `.clone()` it to reuse it.
+#[derive(Clone)]
+pub struct Deta {
+    deta: crate::Deta,
+    runtime: Arc<Runtime>,
+}
+
+impl Deta {
+    /// Creates a new client.
+    ///
+    /// Use this if you have the `Project Key` in the env var `DETA_PROJECT_KEY`.
+    /// Like [`Deta::new`](crate::Deta::new), it reads `DETA_BASE_ENDPOINT` too.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::KeyNotFound`](crate::Error::KeyNotFound)
+    /// * [`Error::InvalidKey`](crate::Error::InvalidKey)
+    /// * [`Error::ClientInitError`](crate::Error::ClientInitError)
+    pub fn new() -> Result<Self> {
+        Self::from_async(crate::Deta::new()?)
+    }
+
+    /// Creates a new client.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: The `Project Key`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::InvalidKey`](crate::Error::InvalidKey)
+    /// * [`Error::ClientInitError`](crate::Error::ClientInitError)
+    pub fn new_with_key(key: impl AsRef<str>) -> Result<Self> {
+        Self::from_async(crate::Deta::new_with_key(key)?)
+    }
+
+    /// Creates a blocking client out of an async one, e.g. one configured with a [`DetaBuilder`](crate::DetaBuilder).
+    ///
+    /// # Arguments
+    ///
+    /// * `deta`: The async client.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::ClientInitError`](crate::Error::ClientInitError)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::DetaBuilder;
+    /// use std::time::Duration;
+    /// # fn main() -> deta::Result<()> {
+    /// let deta = DetaBuilder::new("a0abcdef_thisisaverysecretkey")
+    ///     .timeout(Duration::from_secs(10))
+    ///     .build()?;
+    /// let deta = deta::blocking::Deta::from_async(deta)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_async(deta: crate::Deta) -> Result<Self> {
+        // The worker thread drives the connections and the timers,
+        // so that requests can be blocked on from any thread at once.
+        let runtime = runtime::Builder::new()
+            .threaded_scheduler()
+            .core_threads(1)
+            .thread_name("deta-blocking")
+            .enable_all()
+            .build()
+            .map_err(|e| Error::ClientInitError(e.into()))?;
+
+        Ok(Self {
+            deta,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// Creates a client which keeps the items in memory instead of sending requests.
+    ///
+    /// Only available with the `testing` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `backend`: The in-memory backend. Clients made from clones of it share the same items.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::ClientInitError`](crate::Error::ClientInitError)
+    #[cfg(feature = "testing")]
+    pub fn in_memory(backend: crate::testing::MemoryBackend) -> Result<Self> {
+        Self::from_async(crate::Deta::in_memory(backend))
+    }
+
+    /// Creates a handle to a Deta Base, which shares the `Project Key` and the connections of the client.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_name`: The name of the database.
+    pub fn base(&self, base_name: impl AsRef<str>) -> Base {
+        Base {
+            base: self.deta.base(base_name),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Sets the policy for retrying failed requests.
+    ///
+    /// Like [`Deta::with_retry_policy`](crate::Deta::with_retry_policy),
+    /// it applies to the bases created from the returned client.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy`: The [`RetryPolicy`](crate::RetryPolicy).
+    pub fn with_retry_policy(&self, policy: RetryPolicy) -> Self {
+        Self {
+            deta: self.deta.with_retry_policy(policy),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// The async client, to use it from within an async runtime.
+    pub fn as_async(&self) -> &crate::Deta {
+        &self.deta
+    }
+}
+
+/// A handle to a Deta Base, which blocks on every request.
+///
+/// The methods behave like the ones of [`Base`](crate::Base), and fail with the same errors.
+#[derive(Clone)]
+pub struct Base {
+    base: crate::Base,
+    runtime: Arc<Runtime>,
+}
+
+impl Base {
+    /// The name of the Deta Base.
+    pub fn name(&self) -> &str {
+        self.base.name()
+    }
+
+    /// Get a stored item.
+    ///
+    /// See [`Base::get`](crate::Base::get).
+    pub fn get<T>(&self, key: impl fmt::Display) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        self.block_on(self.base.get(key))
+    }
+
+    /// Get a stored item, or `None` if no item with `key` exists.
+    ///
+    /// See [`Base::get_opt`](crate::Base::get_opt).
+    pub fn get_opt<T>(&self, key: impl fmt::Display) -> Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        self.block_on(self.base.get_opt(key))
+    }
+
+    /// Get a stored item, along with its key and expiry.
+    ///
+    /// See [`Base::get_item`](crate::Base::get_item).
+    pub fn get_item<T>(&self, key: impl fmt::Display) -> Result<Item<T>>
+    where
+        T: DeserializeOwned,
+    {
+        self.block_on(self.base.get_item(key))
+    }
+
+    /// Get all the items matching the query.
+    ///
+    /// See [`Base::query`](crate::Base::query).
+    pub fn query<T>(&self, query: Query) -> Result<Vec<Item<T>>>
+    where
+        T: DeserializeOwned,
+    {
+        self.block_on(self.base.query(query))
+    }
+
+    /// Deletes a stored item.
+    ///
+    /// See [`Base::delete`](crate::Base::delete).
+    pub fn delete(&self, key: impl fmt::Display) -> Result<()> {
+        self.block_on(self.base.delete(key))
+    }
+
+    /// Deletes a stored item, and returns whether it existed.
+    ///
+    /// See [`Base::delete_existing`](crate::Base::delete_existing).
+    pub fn delete_existing(&self, key: impl fmt::Display) -> Result<bool> {
+        self.block_on(self.base.delete_existing(key))
+    }
+
+    /// Stores an item, and returns its key.
+    ///
+    /// See [`Base::put`](crate::Base::put).
+    pub fn put<I>(&self, item: I) -> Result<String>
+    where
+        I: IntoItem,
+    {
+        self.block_on(self.base.put(item))
+    }
+
+    /// Stores multiple items in a single request.
+    ///
+    /// See [`Base::put_many`](crate::Base::put_many).
+    pub fn put_many<I>(&self, items: Vec<I>) -> Result<PutManyReport<I>>
+    where
+        I: IntoItem,
+    {
+        self.block_on(self.base.put_many(items))
+    }
+
+    /// Stores any number of items.
+    ///
+    /// See [`Base::put_all`](crate::Base::put_all).
+    pub fn put_all<I>(&self, items: Vec<I>, concurrency: usize) -> Result<PutManyReport<I>>
+    where
+        I: IntoItem,
+    {
+        self.block_on(self.base.put_all(items, concurrency))
+    }
+
+    /// Stores an item only if no item with the same key exists.
+    ///
+    /// See [`Base::insert`](crate::Base::insert).
+    pub fn insert<I>(&self, item: I) -> Result<String>
+    where
+        I: IntoItem,
+    {
+        self.block_on(self.base.insert(item))
+    }
+
+    /// Updates an item only if an item with `key` exists.
+    ///
+    /// See [`Base::update`](crate::Base::update).
+    pub fn update(&self, key: impl fmt::Display, update: Update) -> Result<()> {
+        self.block_on(self.base.update(key, update))
+    }
+
+    /// The async handle, to use it from within an async runtime.
+    pub fn as_async(&self) -> &crate::Base {
+        &self.base
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.handle().block_on(future)
+    }
+}
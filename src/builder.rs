@@ -0,0 +1,360 @@
+use std::sync::Arc;
+#[cfg(feature = "reqwest")]
+use std::time::Duration;
+
+use http::header;
+#[cfg(feature = "reqwest")]
+use reqwest::{Client, Proxy};
+
+use crate::transport::Transport;
+use crate::{Deta, Error, Result, RetryPolicy, DRIVE_URL, URL};
+
+/// A builder to configure a [`Deta`](crate::Deta) client.
+///
+/// # Examples
+///
+/// ```
+/// use deta::DetaBuilder;
+/// use std::time::Duration;
+/// # fn main() -> deta::Result<()> {
+/// let deta = DetaBuilder::new("a0abcdef_thisisaverysecretkey")
+///     .endpoint("http://localhost:4000/v1")
+///     .timeout(Duration::from_secs(10))
+///     .user_agent("my-app/1.0")
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct DetaBuilder {
+    key: String,
+    endpoint: String,
+    drive_endpoint: String,
+    project_id: Option<String>,
+    #[cfg(feature = "reqwest")]
+    timeout: Option<Duration>,
+    #[cfg(feature = "reqwest")]
+    connect_timeout: Option<Duration>,
+    #[cfg(feature = "reqwest")]
+    user_agent: Option<String>,
+    #[cfg(feature = "reqwest")]
+    headers: header::HeaderMap,
+    #[cfg(feature = "reqwest")]
+    proxies: Vec<Proxy>,
+    transport: Option<Arc<dyn Transport>>,
+    retry: RetryPolicy,
+}
+
+impl DetaBuilder {
+    /// Creates a new builder.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: The `Project Key`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::DetaBuilder;
+    /// let builder = DetaBuilder::new("a0abcdef_thisisaverysecretkey");
+    /// ```
+    pub fn new(key: impl AsRef<str>) -> Self {
+        Self {
+            key: key.as_ref().to_string(),
+            endpoint: URL.to_string(),
+            drive_endpoint: DRIVE_URL.to_string(),
+            project_id: None,
+            #[cfg(feature = "reqwest")]
+            timeout: None,
+            #[cfg(feature = "reqwest")]
+            connect_timeout: None,
+            #[cfg(feature = "reqwest")]
+            user_agent: None,
+            #[cfg(feature = "reqwest")]
+            headers: header::HeaderMap::new(),
+            #[cfg(feature = "reqwest")]
+            proxies: Vec::new(),
+            transport: None,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Sets the URL of the Deta Base API, up to the version.
+    ///
+    /// Defaults to `https://database.deta.sh/v1/`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::DetaBuilder;
+    /// let builder = DetaBuilder::new("a0abcdef_thisisaverysecretkey")
+    ///     .endpoint("http://localhost:4000/v1");
+    /// ```
+    pub fn endpoint(mut self, endpoint: impl AsRef<str>) -> Self {
+        self.endpoint = endpoint.as_ref().to_string();
+        self
+    }
+
+    /// Sets the URL of the Deta Drive API, up to the version.
+    ///
+    /// Defaults to `https://drive.deta.sh/v1/`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::DetaBuilder;
+    /// let builder = DetaBuilder::new("a0abcdef_thisisaverysecretkey")
+    ///     .drive_endpoint("http://localhost:4001/v1");
+    /// ```
+    pub fn drive_endpoint(mut self, endpoint: impl AsRef<str>) -> Self {
+        self.drive_endpoint = endpoint.as_ref().to_string();
+        self
+    }
+
+    /// Sets the ID of the project.
+    ///
+    /// Defaults to the part of the `Project Key` before the first `_`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::DetaBuilder;
+    /// let builder = DetaBuilder::new("a0abcdef_thisisaverysecretkey").project_id("a0abcdef");
+    /// ```
+    pub fn project_id(mut self, project_id: impl AsRef<str>) -> Self {
+        self.project_id = Some(project_id.as_ref().to_string());
+        self
+    }
+
+    /// Sets the timeout of a whole request.
+    ///
+    /// Only available with the `reqwest` feature.
+    /// Ignored if a [`client`](DetaBuilder::client) or a [`transport`](DetaBuilder::transport) is supplied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::DetaBuilder;
+    /// use std::time::Duration;
+    /// let builder = DetaBuilder::new("a0abcdef_thisisaverysecretkey")
+    ///     .timeout(Duration::from_secs(10));
+    /// ```
+    #[cfg(feature = "reqwest")]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the timeout of connecting to the server.
+    ///
+    /// Only available with the `reqwest` feature.
+    /// Ignored if a [`client`](DetaBuilder::client) or a [`transport`](DetaBuilder::transport) is supplied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::DetaBuilder;
+    /// use std::time::Duration;
+    /// let builder = DetaBuilder::new("a0abcdef_thisisaverysecretkey")
+    ///     .connect_timeout(Duration::from_secs(2));
+    /// ```
+    #[cfg(feature = "reqwest")]
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the `User-Agent` header sent with every request.
+    ///
+    /// Only available with the `reqwest` feature.
+    /// Ignored if a [`client`](DetaBuilder::client) or a [`transport`](DetaBuilder::transport) is supplied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::DetaBuilder;
+    /// let builder = DetaBuilder::new("a0abcdef_thisisaverysecretkey").user_agent("my-app/1.0");
+    /// ```
+    #[cfg(feature = "reqwest")]
+    pub fn user_agent(mut self, user_agent: impl AsRef<str>) -> Self {
+        self.user_agent = Some(user_agent.as_ref().to_string());
+        self
+    }
+
+    /// Adds headers sent with every request.
+    ///
+    /// Only available with the `reqwest` feature.
+    /// Ignored if a [`client`](DetaBuilder::client) or a [`transport`](DetaBuilder::transport) is supplied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::http::header::{HeaderMap, HeaderValue};
+    /// use deta::DetaBuilder;
+    ///
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert("x-request-source", HeaderValue::from_static("cron"));
+    /// let builder = DetaBuilder::new("a0abcdef_thisisaverysecretkey").default_headers(headers);
+    /// ```
+    #[cfg(feature = "reqwest")]
+    pub fn default_headers(mut self, headers: header::HeaderMap) -> Self {
+        self.headers.extend(headers);
+        self
+    }
+
+    /// Adds a proxy for the requests.
+    ///
+    /// Only available with the `reqwest` feature.
+    /// Ignored if a [`client`](DetaBuilder::client) or a [`transport`](DetaBuilder::transport) is supplied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::reqwest::Proxy;
+    /// use deta::DetaBuilder;
+    /// # fn main() -> deta::reqwest::Result<()> {
+    /// let builder = DetaBuilder::new("a0abcdef_thisisaverysecretkey")
+    ///     .proxy(Proxy::all("http://localhost:8080")?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "reqwest")]
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxies.push(proxy);
+        self
+    }
+
+    /// Uses an existing `reqwest::Client` for the requests.
+    ///
+    /// Only available with the `reqwest` feature.
+    /// The `Project Key` is still sent with every request,
+    /// but the other options of the client are left as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::reqwest::Client;
+    /// use deta::DetaBuilder;
+    /// let builder = DetaBuilder::new("a0abcdef_thisisaverysecretkey").client(Client::new());
+    /// ```
+    #[cfg(feature = "reqwest")]
+    pub fn client(mut self, client: Client) -> Self {
+        self.transport = Some(Arc::new(client));
+        self
+    }
+
+    /// Sends the requests with a [`Transport`](crate::Transport), instead of a `reqwest::Client`.
+    ///
+    /// The `Project Key` is still sent with every request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::{Deta, DetaBuilder, Transport};
+    ///
+    /// fn client(transport: impl Transport) -> deta::Result<Deta> {
+    ///     DetaBuilder::new("a0abcdef_thisisaverysecretkey")
+    ///         .transport(transport)
+    ///         .build()
+    /// }
+    /// ```
+    pub fn transport(mut self, transport: impl Transport) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Sets the policy for retrying failed requests.
+    ///
+    /// Defaults to [`RetryPolicy::default`](crate::RetryPolicy::default).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::{DetaBuilder, RetryPolicy};
+    /// let builder = DetaBuilder::new("a0abcdef_thisisaverysecretkey")
+    ///     .retry_policy(RetryPolicy::new().max_attempts(5));
+    /// ```
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Creates the client.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::InvalidKey`](crate::Error::InvalidKey)
+    /// * [`Error::ClientInitError`](crate::Error::ClientInitError)
+    /// * [`Error::NoTransport`](crate::Error::NoTransport)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::DetaBuilder;
+    /// # fn main() -> deta::Result<()> {
+    /// let deta = DetaBuilder::new("a0abcdef_thisisaverysecretkey").build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn build(self) -> Result<Deta> {
+        let valid = !self.key.contains(|c: char| {
+            !(c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-' || c == '~')
+        });
+
+        if !valid {
+            return Err(Error::InvalidKey);
+        }
+
+        let key = header::HeaderValue::from_str(&self.key).map_err(|_| Error::InvalidKey)?;
+        let pid = match self.project_id {
+            Some(x) => x,
+            None => self
+                .key
+                .split('_')
+                .next()
+                .ok_or(Error::InvalidKey)?
+                .to_string(),
+        };
+
+        let transport = match self.transport {
+            Some(x) => x,
+            #[cfg(feature = "reqwest")]
+            None => {
+                let mut builder = Client::builder().default_headers(self.headers);
+
+                if let Some(x) = self.timeout {
+                    builder = builder.timeout(x);
+                }
+
+                if let Some(x) = self.connect_timeout {
+                    builder = builder.connect_timeout(x);
+                }
+
+                if let Some(x) = self.user_agent {
+                    builder = builder.user_agent(x);
+                }
+
+                for x in self.proxies {
+                    builder = builder.proxy(x);
+                }
+
+                let client = builder
+                    .build()
+                    .map_err(|e| Error::ClientInitError(e.into()))?;
+                Arc::new(client)
+            }
+            #[cfg(not(feature = "reqwest"))]
+            None => return Err(Error::NoTransport),
+        };
+
+        let url = Arc::new(format!("{}/{}", self.endpoint.trim_end_matches('/'), pid));
+        let drive_url = format!("{}/{}", self.drive_endpoint.trim_end_matches('/'), pid);
+
+        Ok(Deta {
+            transport,
+            url,
+            drive_url: Arc::new(drive_url),
+            key,
+            retry: self.retry,
+        })
+    }
+}
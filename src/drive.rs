@@ -0,0 +1,674 @@
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::io::{AsyncRead, AsyncReadExt};
+use futures::stream::{self, Stream, TryStreamExt};
+use http::{header, request, Method, Request, Response, StatusCode};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::transport::{Body, Transport};
+use crate::{Error, ErrorResult, RequestError, Result};
+
+/// The maximum number of files in a single delete request.
+const MAX_NAMES: usize = 1000;
+/// The size of a part of a chunked upload.
+const PART_SIZE: usize = 10 * 1024 * 1024;
+/// The number of parts of a chunked upload sent at the same time.
+const PART_CONCURRENCY: usize = 4;
+
+/// The characters escaped in the values of a query string.
+const QUERY_VALUE: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// A handle to a Deta Drive.
+///
+/// Get one with [`Deta::drive`](crate::Deta::drive).
+/// It shares the `Project Key` and the [`Transport`](crate::Transport) of the `Deta` client,
+/// so `.clone()` it to reuse it.
+///
+/// Requests to a Drive aren't retried.
+///
+/// # Examples
+///
+/// ```no_run
+/// use deta::Deta;
+/// # #[tokio::main]
+/// # async fn main() -> deta::Result<()> {
+/// let deta = Deta::new()?;
+///
+/// let drive = deta.drive("photos");
+/// drive.put("hello.txt", "Hello, World!").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Drive {
+    transport: Arc<dyn Transport>,
+    url: Arc<String>,
+    key: header::HeaderValue,
+}
+
+/// A page of the names of files in a Drive.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FilePage {
+    /// The names of the files.
+    pub names: Vec<String>,
+
+    /// The name to continue listing after, if there are more files.
+    pub last: Option<String>,
+}
+
+/// The result of deleting files from a Drive.
+#[derive(Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct Deleted {
+    /// The names of the files which were deleted.
+    #[serde(default)]
+    pub deleted: Vec<String>,
+
+    /// The names of the files which couldn't be deleted, with the reason.
+    #[serde(default)]
+    pub failed: HashMap<String, String>,
+}
+
+impl Drive {
+    pub(crate) fn new(
+        transport: Arc<dyn Transport>,
+        url: String,
+        key: header::HeaderValue,
+    ) -> Self {
+        Self {
+            transport,
+            url: Arc::new(url),
+            key,
+        }
+    }
+
+    /// Stores a file.
+    /// This request overwrites a file if the name already exists.
+    ///
+    /// Returns the name, if successful.
+    ///
+    /// A single request can store up to 10 MB.
+    /// Use [`put_stream`](Drive::put_stream) or [`upload_stream`](Drive::upload_stream)
+    /// for streams and larger files.
+    ///
+    /// # Arguments
+    ///
+    /// * `name`: The name of the file, which may contain `/` to put it in a folder.
+    /// * `body`: The contents of the file, like bytes or a `String`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
+    /// * [`Error::BadRequest`](crate::Error::BadRequest)
+    /// * [`Error::ServerError`](crate::Error::ServerError)
+    /// * [`Error::JSONDeserializingFailed`](crate::Error::JSONDeserializingFailed)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use deta::Deta;
+    /// # #[tokio::main]
+    /// # async fn main() -> deta::Result<()> {
+    /// let drive = Deta::new()?.drive("files");
+    ///
+    /// drive.put("hello.txt", "Hello, World!").await?;
+    /// drive.put("data/bytes.bin", vec![0u8, 1, 2, 3]).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn put(&self, name: impl AsRef<str>, body: impl Into<Bytes>) -> Result<String> {
+        self.upload(name.as_ref(), None, body.into()).await
+    }
+
+    /// Stores a file with a `Content-Type`.
+    /// This request overwrites a file if the name already exists.
+    ///
+    /// Returns the name, if successful.
+    ///
+    /// # Arguments
+    ///
+    /// * `name`: The name of the file.
+    /// * `content_type`: The `Content-Type` the file is served with.
+    /// * `body`: The contents of the file.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::InvalidContentType`](crate::Error::InvalidContentType)
+    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
+    /// * [`Error::BadRequest`](crate::Error::BadRequest)
+    /// * [`Error::ServerError`](crate::Error::ServerError)
+    /// * [`Error::JSONDeserializingFailed`](crate::Error::JSONDeserializingFailed)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use deta::Deta;
+    /// # #[tokio::main]
+    /// # async fn main() -> deta::Result<()> {
+    /// let drive = Deta::new()?.drive("files");
+    /// drive
+    ///     .put_with_content_type("index.html", "text/html", "<h1>Hello</h1>")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn put_with_content_type(
+        &self,
+        name: impl AsRef<str>,
+        content_type: impl AsRef<str>,
+        body: impl Into<Bytes>,
+    ) -> Result<String> {
+        let content_type = header::HeaderValue::from_str(content_type.as_ref())
+            .map_err(|_| Error::InvalidContentType)?;
+
+        self.upload(name.as_ref(), Some(content_type), body.into())
+            .await
+    }
+
+    /// Stores a file read from a stream of chunks.
+    /// This request overwrites a file if the name already exists.
+    ///
+    /// Returns the name, if successful.
+    ///
+    /// A [`Transport`](crate::Transport) only sends whole bodies,
+    /// so the stream is sent with a chunked upload, like [`upload_stream`](Drive::upload_stream),
+    /// and at most 40 MB of it are held in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `name`: The name of the file.
+    /// * `body`: The chunks of the file.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::ReadError`](crate::Error::ReadError)
+    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
+    /// * [`Error::BadRequest`](crate::Error::BadRequest)
+    /// * [`Error::ServerError`](crate::Error::ServerError)
+    /// * [`Error::JSONDeserializingFailed`](crate::Error::JSONDeserializingFailed)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use deta::Deta;
+    /// use futures::stream;
+    /// # #[tokio::main]
+    /// # async fn main() -> deta::Result<()> {
+    /// let drive = Deta::new()?.drive("files");
+    ///
+    /// let chunks = stream::iter(vec![Ok("Hello, "), Ok("World!")]);
+    /// drive.put_stream("hello.txt", chunks).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn put_stream<S, B>(&self, name: impl AsRef<str>, body: S) -> Result<String>
+    where
+        S: Stream<Item = io::Result<B>>,
+        B: AsRef<[u8]>,
+    {
+        self.upload_stream(name, Box::pin(body).into_async_read())
+            .await
+    }
+
+    /// Gets a file.
+    ///
+    /// Returns the contents of the file as a stream of chunks.
+    ///
+    /// # Arguments
+    ///
+    /// * `name`: The name of the file.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
+    /// * [`Error::FileNotFound`](crate::Error::FileNotFound)
+    /// * [`Error::ServerError`](crate::Error::ServerError)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use deta::Deta;
+    /// use futures::TryStreamExt;
+    /// # #[tokio::main]
+    /// # async fn main() -> deta::Result<()> {
+    /// let drive = Deta::new()?.drive("files");
+    ///
+    /// let mut file = Box::pin(drive.get("hello.txt").await?);
+    /// while let Some(chunk) = file.try_next().await? {
+    ///     println!("{} bytes", chunk.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get(&self, name: impl AsRef<str>) -> Result<impl Stream<Item = Result<Bytes>>> {
+        let query = [("name", name.as_ref())];
+        let (response, request) = self
+            .send(
+                Method::GET,
+                "files/download",
+                &query,
+                |x| x.body(Bytes::new()),
+                |e| {
+                    if e.status() == Some(StatusCode::NOT_FOUND) {
+                        Error::FileNotFound(e)
+                    } else {
+                        Error::from_status(e)
+                    }
+                },
+            )
+            .await?;
+
+        Ok(response.into_body().into_stream().map_err(move |e| {
+            let request = RequestError::new(request.method().clone(), request.url().to_string());
+            Error::RequestSendError(request.with_source(e))
+        }))
+    }
+
+    /// Lists the names of all the files starting with a prefix.
+    ///
+    /// This follows the pages of the listing until there are no more files.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix`: The prefix of the names, or `""` for every file.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
+    /// * [`Error::BadRequest`](crate::Error::BadRequest)
+    /// * [`Error::ServerError`](crate::Error::ServerError)
+    /// * [`Error::JSONDeserializingFailed`](crate::Error::JSONDeserializingFailed)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use deta::Deta;
+    /// # #[tokio::main]
+    /// # async fn main() -> deta::Result<()> {
+    /// let drive = Deta::new()?.drive("files");
+    /// let names = drive.list("data/").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list(&self, prefix: impl AsRef<str>) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        let mut last = None;
+
+        loop {
+            let page = self.list_page(prefix.as_ref(), None, last).await?;
+            names.extend(page.names);
+
+            match page.last {
+                Some(x) => last = Some(x),
+                None => return Ok(names),
+            }
+        }
+    }
+
+    /// Lists a single page of the names of the files starting with a prefix.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix`: The prefix of the names, or `""` for every file.
+    /// * `limit`: The maximum number of names in the page. Deta defaults to 1000.
+    /// * `last`: The [`last`](FilePage::last) name of the previous page, to continue after it.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
+    /// * [`Error::BadRequest`](crate::Error::BadRequest)
+    /// * [`Error::ServerError`](crate::Error::ServerError)
+    /// * [`Error::JSONDeserializingFailed`](crate::Error::JSONDeserializingFailed)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use deta::Deta;
+    /// # #[tokio::main]
+    /// # async fn main() -> deta::Result<()> {
+    /// let drive = Deta::new()?.drive("files");
+    ///
+    /// let first = drive.list_page("data/", Some(10), None).await?;
+    /// if let Some(last) = first.last {
+    ///     let second = drive.list_page("data/", Some(10), Some(last)).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_page(
+        &self,
+        prefix: impl AsRef<str>,
+        limit: Option<usize>,
+        last: Option<String>,
+    ) -> Result<FilePage> {
+        let limit = limit.map(|x| x.to_string());
+        let mut query = vec![("prefix", prefix.as_ref())];
+        query.extend(limit.as_deref().map(|x| ("limit", x)));
+        query.extend(last.as_deref().map(|x| ("last", x)));
+
+        let (response, _) = self
+            .send(
+                Method::GET,
+                "files",
+                &query,
+                |x| x.body(Bytes::new()),
+                Error::from_status,
+            )
+            .await?;
+        let list: FileList = json(response).await?;
+
+        Ok(FilePage {
+            names: list.names,
+            last: list.paging.and_then(|x| x.last),
+        })
+    }
+
+    /// Deletes files.
+    ///
+    /// Names which don't exist are reported as deleted.
+    ///
+    /// # Arguments
+    ///
+    /// * `names`: The names of the files, at most 1000.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::VecTooLong`](crate::Error::VecTooLong)
+    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
+    /// * [`Error::BadRequest`](crate::Error::BadRequest)
+    /// * [`Error::ServerError`](crate::Error::ServerError)
+    /// * [`Error::JSONDeserializingFailed`](crate::Error::JSONDeserializingFailed)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use deta::Deta;
+    /// # #[tokio::main]
+    /// # async fn main() -> deta::Result<()> {
+    /// let drive = Deta::new()?.drive("files");
+    ///
+    /// let result = drive.delete_many(vec!["hello.txt", "data/bytes.bin"]).await?;
+    /// assert!(result.failed.is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete_many<S>(&self, names: Vec<S>) -> Result<Deleted>
+    where
+        S: AsRef<str>,
+    {
+        if names.len() > MAX_NAMES {
+            return Err(Error::VecTooLong);
+        }
+
+        let names = names.iter().map(|x| x.as_ref()).collect::<Vec<_>>();
+        let body = serde_json::to_vec(&serde_json::json!({ "names": names }))
+            .map_err(Error::JSONSerializingFailed)?;
+
+        let (response, _) = self
+            .send(
+                Method::DELETE,
+                "files",
+                &[],
+                |x| {
+                    x.header(header::CONTENT_TYPE, "application/json")
+                        .body(body.into())
+                },
+                Error::from_status,
+            )
+            .await?;
+
+        json(response).await
+    }
+
+    /// Stores a file of any size, read from `reader`, with a chunked upload.
+    /// This request overwrites a file if the name already exists.
+    ///
+    /// Returns the name, if successful.
+    ///
+    /// The file is split into parts of 10 MB, and up to 4 parts are sent at the same time,
+    /// so at most 40 MB of the file are held in memory.
+    /// If reading or sending a part fails, the upload is aborted and nothing is stored.
+    ///
+    /// # Arguments
+    ///
+    /// * `name`: The name of the file.
+    /// * `reader`: The contents of the file.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::ReadError`](crate::Error::ReadError)
+    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
+    /// * [`Error::BadRequest`](crate::Error::BadRequest)
+    /// * [`Error::ServerError`](crate::Error::ServerError)
+    /// * [`Error::JSONDeserializingFailed`](crate::Error::JSONDeserializingFailed)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use deta::Deta;
+    /// use futures::io::Cursor;
+    /// # #[tokio::main]
+    /// # async fn main() -> deta::Result<()> {
+    /// let drive = Deta::new()?.drive("backups");
+    ///
+    /// let backup = Cursor::new(vec![0u8; 64 * 1024 * 1024]);
+    /// drive.upload_stream("backup.tar", backup).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn upload_stream<R>(&self, name: impl AsRef<str>, reader: R) -> Result<String>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let name = name.as_ref();
+        let (response, _) = self
+            .send(
+                Method::POST,
+                "uploads",
+                &[("name", name)],
+                |x| x.body(Bytes::new()),
+                Error::from_status,
+            )
+            .await?;
+        let upload: Upload = json(response).await?;
+        let path = format!("uploads/{}", upload.upload_id);
+
+        let result = async {
+            self.upload_parts(name, &path, reader).await?;
+            self.send(
+                Method::PATCH,
+                &path,
+                &[("name", name)],
+                |x| x.body(Bytes::new()),
+                Error::from_status,
+            )
+            .await
+        }
+        .await;
+
+        match result {
+            Ok(_) => Ok(upload.name),
+            Err(e) => {
+                // The error of the upload is more useful than the error of aborting it.
+                let _ = self
+                    .send(
+                        Method::DELETE,
+                        &path,
+                        &[("name", name)],
+                        |x| x.body(Bytes::new()),
+                        Error::from_status,
+                    )
+                    .await;
+
+                Err(e)
+            }
+        }
+    }
+
+    /// Reads the parts of a chunked upload and sends them.
+    ///
+    /// A part is only read once there's room to send it, to bound the memory used.
+    async fn upload_parts<R>(&self, name: &str, path: &str, reader: R) -> Result<()>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let path = format!("{}/parts", path);
+
+        stream::try_unfold((reader, 1usize), |(mut reader, number)| async move {
+            let mut part = Vec::new();
+            (&mut reader)
+                .take(PART_SIZE as u64)
+                .read_to_end(&mut part)
+                .await
+                .map_err(Error::ReadError)?;
+
+            // An empty file still needs a single, empty part.
+            if part.is_empty() && number > 1 {
+                return Ok(None);
+            }
+
+            Ok(Some(((number, part), (reader, number + 1))))
+        })
+        .map_ok(|(number, part)| {
+            let number = number.to_string();
+            let path = &path;
+
+            async move {
+                let query = [("name", name), ("part", &number)];
+                self.send(
+                    Method::POST,
+                    path,
+                    &query,
+                    |x| x.body(part.into()),
+                    Error::from_status,
+                )
+                .await
+                .map(|_| ())
+            }
+        })
+        .try_buffer_unordered(PART_CONCURRENCY)
+        .try_collect()
+        .await
+    }
+
+    async fn upload(
+        &self,
+        name: &str,
+        content_type: Option<header::HeaderValue>,
+        body: Bytes,
+    ) -> Result<String> {
+        let (response, _) = self
+            .send(
+                Method::POST,
+                "files",
+                &[("name", name)],
+                |x| match content_type {
+                    Some(content_type) => x.header(header::CONTENT_TYPE, content_type).body(body),
+                    None => x.body(body),
+                },
+                Error::from_status,
+            )
+            .await?;
+        let file: File = json(response).await?;
+
+        Ok(file.name)
+    }
+
+    /// Sends a request to the drive.
+    ///
+    /// Returns the response along with the details of the request.
+    /// Responses with a status code other than a success are turned into an error by `error`.
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        build: impl FnOnce(request::Builder) -> http::Result<Request<Bytes>>,
+        error: impl FnOnce(RequestError) -> Error,
+    ) -> Result<(Response<Body>, RequestError)> {
+        let url = format!("{}/{}", self.url, path);
+        let request = RequestError::new(method.clone(), url.clone());
+
+        let query = query
+            .iter()
+            .map(|(name, x)| format!("{}={}", name, utf8_percent_encode(x, QUERY_VALUE)))
+            .collect::<Vec<_>>();
+        let uri = if query.is_empty() {
+            url
+        } else {
+            format!("{}?{}", url, query.join("&"))
+        };
+
+        let builder = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("x-api-key", self.key.clone());
+
+        let response = match build(builder) {
+            Ok(x) => self.transport.send(x).await,
+            Err(e) => Err(e.into()),
+        };
+        let response = match response {
+            Ok(x) => x,
+            Err(e) => return Err(Error::RequestSendError(request.with_source(e))),
+        };
+
+        let status = response.status();
+        if !status.is_success() {
+            let messages = match response.into_body().bytes().await {
+                Ok(x) => serde_json::from_slice::<ErrorResult>(&x)
+                    .map(|x| x.errors)
+                    .unwrap_or_default(),
+                Err(_) => Vec::new(),
+            };
+            return Err(error(request.with_status(status, messages)));
+        }
+
+        Ok((response, request))
+    }
+}
+
+/// Receives and deserializes the JSON body of a response.
+async fn json<T>(response: Response<Body>) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    let body = response
+        .into_body()
+        .bytes()
+        .await
+        .map_err(Error::deserializing)?;
+
+    serde_json::from_slice(&body).map_err(Error::JSONDeserializingFailed)
+}
+
+#[derive(Deserialize)]
+struct File {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Upload {
+    name: String,
+    upload_id: String,
+}
+
+#[derive(Deserialize)]
+struct Paging {
+    last: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct FileList {
+    paging: Option<Paging>,
+    #[serde(default)]
+    names: Vec<String>,
+}
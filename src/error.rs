@@ -1,3 +1,9 @@
+use std::time::Duration;
+use std::{error, fmt, io};
+
+use http::{Method, StatusCode};
+
+use crate::transport::TransportError;
 use thiserror::Error;
 
 /// Errors that may occur.
@@ -15,70 +21,293 @@ pub enum Error {
     #[error("invalid key")]
     InvalidKey,
 
+    /// The key of an item is invalid.
+    ///
+    /// A key can't be empty, `.` or `..`.
+    #[error("invalid item key {0:?}")]
+    InvalidItemKey(String),
+
     /// Error occurred while initializing client.
     ///
     /// This occurs when the TLS backend cannot be initialized,
     /// or the resolver cannot load the system configuration.
     #[error("error while initializing client")]
-    ClientInitError,
+    ClientInitError(#[source] TransportError),
 
-    /// Base name not present.
+    /// No transport was set, and the default `reqwest` feature isn't enabled.
     ///
-    /// You might have initialized the Deta client, but not assigned it to a Deta Base.
-    #[error("base name not present")]
-    BaseNameNotPresent,
+    /// Set one with [`DetaBuilder::transport`](crate::DetaBuilder::transport).
+    #[error("no transport set")]
+    NoTransport,
+
+    /// The content type supplied isn't a valid header value.
+    #[error("invalid content type")]
+    InvalidContentType,
+
+    /// Error occurred while reading a file to upload.
+    #[error("error while reading file")]
+    ReadError(#[source] io::Error),
 
     /// The length of the vector is too long.
     ///
-    /// There can't be more than 25 items at a time.
+    /// There can't be more than 25 items, or 1000 file names, at a time.
     #[error("vec length too long")]
     VecTooLong,
 
+    /// An item is too large to be stored.
+    ///
+    /// An item can't be larger than 400 KB once serialized to JSON.
+    #[error("item {key:?} is {size} bytes, larger than 400 KB")]
+    ItemTooLarge {
+        /// The key of the item, if it has one.
+        key: Option<String>,
+        /// The size of the item serialized to JSON, in bytes.
+        size: usize,
+    },
+
+    /// Several items have the same key.
+    #[error("duplicate item key {0:?}")]
+    DuplicateKey(String),
+
     /// Error occurred while sending a request.
     ///
     /// Check your network connectivity.
     #[error("error while sending request")]
-    RequestSendError,
+    RequestSendError(#[source] RequestError),
 
     /// Item not found in the Deta Base.
     #[error("item not found")]
-    ItemNotFound,
+    ItemNotFound(#[source] RequestError),
+
+    /// File not found in the Deta Drive.
+    #[error("file not found")]
+    FileNotFound(#[source] RequestError),
 
     /// Key already exists in the Deta Base.
     #[error("key already exists")]
-    KeyConflict,
+    KeyConflict(#[source] RequestError),
 
     /// Key doesn't exist in the Deta Base.
     #[error("key doesn't exist")]
-    KeyNonexistent,
+    KeyNonexistent(#[source] RequestError),
 
     /// Bad Request.
     ///
-    /// Occurs in the following cases:
-    /// * If the number of items in the requests exceeds 25
-    /// * If total request size exceeds 16 MB
-    /// * If any individual item exceeds 400KB
-    /// * If there are two items with identical keys
+    /// The request was rejected as invalid.
+    /// The number of items, their size and their keys are checked before sending a request,
+    /// failing with [`VecTooLong`](Error::VecTooLong), [`ItemTooLarge`](Error::ItemTooLarge)
+    /// or [`DuplicateKey`](Error::DuplicateKey) instead.
+    /// 25 items of at most 400 KB always fit in the 16 MB limit of a request.
     #[error("bad request")]
-    BadRequest,
+    BadRequest(#[source] RequestError),
+
+    /// The `Project Key` was rejected.
+    ///
+    /// Ensure that the key is valid and hasn't been revoked.
+    #[error("unauthorized")]
+    Unauthorized(#[source] RequestError),
+
+    /// The `Project Key` isn't allowed to access the Deta Base or the Deta Drive.
+    #[error("forbidden")]
+    Forbidden(#[source] RequestError),
+
+    /// Too many requests were sent, and the request was rate limited.
+    ///
+    /// The server may tell how long to wait, see [`RequestError::retry_after`].
+    #[error("too many requests")]
+    TooManyRequests(#[source] RequestError),
 
     /// Server error.
     ///
-    /// The server didn't return the expected response.
+    /// The server failed with a `5xx` status code, or didn't return the expected response.
     #[error("server error")]
-    ServerError,
+    ServerError(#[source] RequestError),
+
+    /// The Deta Base reported the item as failed, and didn't store it.
+    ///
+    /// Contains the item, as it was sent.
+    #[error("item rejected")]
+    ItemRejected(serde_json::Value),
 
     /// Serializing to JSON failed.
     ///
     /// The request didn't successfully serialize to JSON.
+    /// The source tells which value failed.
     #[error("JSON serializing failed")]
-    JSONSerializingFailed,
+    JSONSerializingFailed(#[source] serde_json::Error),
 
     /// Deserializing to JSON failed.
     ///
     /// The response didn't successfully deserialize to JSON.
+    /// The source tells which field failed.
     #[error("JSON deserializing failed")]
-    JSONDeserializingFailed,
+    JSONDeserializingFailed(#[source] serde_json::Error),
+}
+
+impl Error {
+    /// Maps a failed response to an error by its status code.
+    pub(crate) fn from_status(error: RequestError) -> Self {
+        match error.status() {
+            Some(StatusCode::BAD_REQUEST) => Self::BadRequest(error),
+            Some(StatusCode::UNAUTHORIZED) => Self::Unauthorized(error),
+            Some(StatusCode::FORBIDDEN) => Self::Forbidden(error),
+            Some(StatusCode::NOT_FOUND) => Self::ItemNotFound(error),
+            Some(StatusCode::CONFLICT) => Self::KeyConflict(error),
+            Some(StatusCode::TOO_MANY_REQUESTS) => Self::TooManyRequests(error),
+            _ => Self::ServerError(error),
+        }
+    }
+
+    /// A serializing error which `serde_json` didn't raise, like a value which isn't an object.
+    pub(crate) fn serializing(message: impl fmt::Display) -> Self {
+        Self::JSONSerializingFailed(serde::ser::Error::custom(message))
+    }
+
+    /// A deserializing error which `serde_json` didn't raise, like a missing attribute.
+    pub(crate) fn deserializing(message: impl fmt::Display) -> Self {
+        Self::JSONDeserializingFailed(serde::de::Error::custom(message))
+    }
+
+    /// The details of the request, if the error occurred while making a request.
+    pub fn request(&self) -> Option<&RequestError> {
+        match self {
+            Self::RequestSendError(x)
+            | Self::ItemNotFound(x)
+            | Self::FileNotFound(x)
+            | Self::KeyConflict(x)
+            | Self::KeyNonexistent(x)
+            | Self::BadRequest(x)
+            | Self::Unauthorized(x)
+            | Self::Forbidden(x)
+            | Self::TooManyRequests(x)
+            | Self::ServerError(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    /// The status code of the response, if a response was received.
+    pub fn status(&self) -> Option<StatusCode> {
+        self.request().and_then(RequestError::status)
+    }
+
+    /// Whether the error occurred because the item or the file doesn't exist.
+    pub fn is_not_found(&self) -> bool {
+        matches!(
+            self,
+            Self::ItemNotFound(_) | Self::FileNotFound(_) | Self::KeyNonexistent(_)
+        )
+    }
+
+    /// Whether sending the same request again might succeed.
+    ///
+    /// This is the case for network errors, rate limiting and server errors.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::RequestSendError(x) => !x.is_final(),
+            _ => self.status().is_some_and(crate::retry::is_retryable),
+        }
+    }
+}
+
+/// The details of a request which failed.
+///
+/// The key of the item is redacted from the URL, and the `Project Key` is never included.
+#[derive(Debug)]
+pub struct RequestError {
+    method: Method,
+    url: String,
+    status: Option<StatusCode>,
+    messages: Vec<String>,
+    retry_after: Option<Duration>,
+    source: Option<TransportError>,
+}
+
+impl RequestError {
+    pub(crate) fn new(method: Method, url: String) -> Self {
+        Self {
+            method,
+            url,
+            status: None,
+            messages: Vec::new(),
+            retry_after: None,
+            source: None,
+        }
+    }
+
+    pub(crate) fn with_status(mut self, status: StatusCode, messages: Vec<String>) -> Self {
+        self.status = Some(status);
+        self.messages = messages;
+        self
+    }
+
+    pub(crate) fn with_retry_after(mut self, retry_after: Option<Duration>) -> Self {
+        self.retry_after = retry_after;
+        self
+    }
+
+    pub(crate) fn with_source(mut self, source: TransportError) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// Whether the request failed in a way which sending it again can't fix.
+    pub(crate) fn is_final(&self) -> bool {
+        #[cfg(feature = "testing")]
+        {
+            let unrecorded = |x: &TransportError| x.is::<crate::testing::Unrecorded>();
+            self.source.as_ref().is_some_and(unrecorded)
+        }
+
+        #[cfg(not(feature = "testing"))]
+        false
+    }
+
+    /// The method of the request.
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// The URL of the request, with the key of the item redacted.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The status code of the response, if a response was received.
+    pub fn status(&self) -> Option<StatusCode> {
+        self.status
+    }
+
+    /// The error messages sent by the server.
+    pub fn messages(&self) -> &[String] {
+        &self.messages
+    }
+
+    /// How long the server asked to wait before retrying, with the `Retry-After` header.
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.retry_after
+    }
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.method, self.url)?;
+
+        if let Some(x) = self.status {
+            write!(f, " responded with {}", x)?;
+        }
+
+        if !self.messages.is_empty() {
+            write!(f, ": {}", self.messages.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl error::Error for RequestError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.source.as_ref().map(|x| &**x as _)
+    }
 }
 
 /// A `Result` alias where the `Err` case is `deta::Error`.
@@ -1,12 +1,37 @@
 use std::string;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use serde::de::{self, DeserializeOwned};
 use serde::{Deserialize, Serialize};
 
+use crate::{Error, Result};
+
 /// An item which is sent or retrieved from Deta Base.
-#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+///
+/// Deta Base stores every item as an object of attributes with a `key` attribute.
+/// A value which serializes to an object is stored as the attributes themselves,
+/// and any other value, like a number or a list, is stored in a `value` attribute.
+///
+/// When an item is read back, `key` and `__expires` are taken out of the attributes,
+/// and the rest is read into `T` by its type, never by trying what fits:
+///
+/// * If `T` deserializes from an object, like a struct or a map, it's read from all the attributes,
+///   so a struct with a single field named `value` round-trips.
+/// * Otherwise, like for a number, a list, an enum or a `serde_json::Value`,
+///   it's read from the `value` attribute if the attributes are exactly `{"value": ...}`,
+///   and from all the attributes if not.
+///
+/// An `Option` follows the type it wraps.
+/// The key is never read into `T`, even if `T` is a [`DetaItem`].
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
 pub struct Item<T> {
     pub key: Option<String>,
     pub value: T,
+    /// The time at which the item expires, in seconds since the Unix epoch.
+    ///
+    /// Stored as the `__expires` attribute of the item.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<u64>,
 }
 
 impl<T> Item<T> {
@@ -22,7 +47,11 @@ impl<T> Item<T> {
     /// assert_eq!(item.value, 5);
     /// ```
     pub fn new(value: T) -> Self {
-        Self { key: None, value }
+        Self {
+            key: None,
+            value,
+            expires: None,
+        }
     }
 
     /// Make a new item with a key and a value.
@@ -40,6 +69,312 @@ impl<T> Item<T> {
         Self {
             key: Some(key.to_string()),
             value,
+            expires: None,
+        }
+    }
+
+    /// Make the item expire after a duration from now.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::Item;
+    /// use std::time::Duration;
+    ///
+    /// let item = Item::new_with_key("session", 5).expire_in(Duration::from_secs(300));
+    /// assert!(item.expires.is_some());
+    /// ```
+    pub fn expire_in(self, duration: Duration) -> Self {
+        self.expire_at(SystemTime::now() + duration)
+    }
+
+    /// Make the item expire at a point in time.
+    ///
+    /// The time is rounded down to the second.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::Item;
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    /// let item = Item::new_with_key("session", 5).expire_at(time);
+    /// assert_eq!(item.expires, Some(1_700_000_000));
+    /// ```
+    pub fn expire_at(mut self, time: SystemTime) -> Self {
+        self.expires = Some(timestamp(time));
+        self
+    }
+
+    /// The time at which the item expires, if it expires.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::Item;
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    /// let item = Item::new_with_key("session", 5).expire_at(time);
+    /// assert_eq!(item.expires_at(), Some(time));
+    /// ```
+    pub fn expires_at(&self) -> Option<SystemTime> {
+        self.expires.map(|x| UNIX_EPOCH + Duration::from_secs(x))
+    }
+}
+
+impl<T> Item<T>
+where
+    T: Serialize,
+{
+    /// Makes a Deta Base item out of an item.
+    ///
+    /// Values which aren't objects are wrapped in an object with a `value` attribute.
+    pub(crate) fn to_value(&self) -> Result<serde_json::Value> {
+        let mut value = serde_json::to_value(&self.value).map_err(Error::JSONSerializingFailed)?;
+
+        if !value.is_object() {
+            value = serde_json::json!({ "value": value });
+        }
+
+        if let Some(x) = &self.key {
+            validate_key(x)?;
+            value["key"] = serde_json::json!(x);
+        }
+
+        if let Some(x) = self.expires {
+            value["__expires"] = serde_json::json!(x);
+        }
+
+        Ok(value)
+    }
+}
+
+impl<T> Item<T>
+where
+    T: DeserializeOwned,
+{
+    /// Makes an item out of a stored Deta Base item, following the rule documented on [`Item`].
+    pub(crate) fn from_value(mut value: serde_json::Value) -> Result<Self> {
+        let object = value
+            .as_object_mut()
+            .ok_or_else(|| Error::deserializing("the stored item isn't an object"))?;
+        let expires = match object.remove("__expires") {
+            Some(x) => Some(
+                x.as_u64()
+                    .ok_or_else(|| Error::deserializing("__expires isn't a timestamp"))?,
+            ),
+            None => None,
+        };
+
+        let key = match object.remove("key") {
+            Some(serde_json::Value::String(x)) => Some(x),
+            Some(_) => return Err(Error::deserializing("the key isn't a string")),
+            None => None,
+        };
+
+        if !is_object::<T>() && object.len() == 1 {
+            if let Some(x) = object.remove("value") {
+                value = x;
+            }
+        }
+        let value = serde_json::from_value(value).map_err(Error::JSONDeserializingFailed)?;
+
+        Ok(Self {
+            key,
+            value,
+            expires,
+        })
+    }
+}
+
+/// A struct stored as an item, with the key in one of its fields.
+///
+/// With the `derive` feature, derive it with `#[derive(DetaItem)]`,
+/// marking the key field with `#[deta(key)]`.
+/// The key field is stored as the `key` of the item, and the other fields as the attributes.
+/// It may be a `String`, or an `Option<String>` to let Deta generate the key.
+///
+/// Values of these structs can be stored directly with [`Base::put`](crate::Base::put)
+/// and the other methods taking an [`IntoItem`](crate::IntoItem),
+/// and are read back with their key by [`Base::get_keyed`](crate::Base::get_keyed)
+/// and [`Base::query_keyed`](crate::Base::query_keyed).
+/// The other reads, like [`TypedBase::get`](crate::TypedBase::get), only read the attributes,
+/// so they leave the key field missing.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "derive")]
+/// # {
+/// use deta::DetaItem;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize, DetaItem)]
+/// struct User {
+///     #[deta(key)]
+///     id: String,
+///     name: String,
+/// }
+/// # }
+/// ```
+pub trait DetaItem: Serialize + DeserializeOwned {
+    /// The serialized name of the field holding the key.
+    const KEY_FIELD: &'static str;
+}
+
+/// Values which can be stored as an item.
+///
+/// This is implemented by [`Item`](crate::Item), and by the structs implementing [`DetaItem`].
+pub trait IntoItem {
+    /// The type of the value stored.
+    type Value;
+
+    #[doc(hidden)]
+    fn to_value(&self) -> Result<serde_json::Value>;
+}
+
+impl<T> IntoItem for Item<T>
+where
+    T: Serialize,
+{
+    type Value = T;
+
+    fn to_value(&self) -> Result<serde_json::Value> {
+        Item::to_value(self)
+    }
+}
+
+impl<T> IntoItem for T
+where
+    T: DetaItem,
+{
+    type Value = T;
+
+    fn to_value(&self) -> Result<serde_json::Value> {
+        let mut value = serde_json::to_value(self).map_err(Error::JSONSerializingFailed)?;
+        let object = value
+            .as_object_mut()
+            .ok_or_else(|| Error::serializing("the item isn't a struct"))?;
+
+        match object.remove(T::KEY_FIELD) {
+            Some(serde_json::Value::String(x)) => {
+                validate_key(&x)?;
+                object.insert("key".to_string(), serde_json::Value::String(x));
+            }
+            Some(serde_json::Value::Null) | None => {}
+            Some(_) => {
+                let message = format!("the key field {} isn't a string", T::KEY_FIELD);
+                return Err(Error::serializing(message));
+            }
         }
+
+        Ok(value)
+    }
+}
+
+/// Deserializes a struct stored as an item, moving the key into its key field.
+pub(crate) fn from_keyed_value<T>(mut value: serde_json::Value) -> Result<T>
+where
+    T: DetaItem,
+{
+    let object = value
+        .as_object_mut()
+        .ok_or_else(|| Error::deserializing("the stored item isn't an object"))?;
+    object.remove("__expires");
+
+    if let Some(x) = object.remove("key") {
+        object.insert(T::KEY_FIELD.to_string(), x);
     }
+
+    serde_json::from_value(value).map_err(Error::JSONDeserializingFailed)
+}
+
+/// Whether `T` deserializes from an object, like a struct or a map.
+///
+/// This only depends on the type, which asks the deserializer for its shape.
+fn is_object<T>() -> bool
+where
+    T: DeserializeOwned,
+{
+    let mut object = false;
+    let _ = T::deserialize(ShapeProbe(&mut object));
+
+    object
+}
+
+/// A deserializer which only records whether a type asks for an object.
+struct ShapeProbe<'a>(&'a mut bool);
+
+impl<'de, 'a> de::Deserializer<'de> for ShapeProbe<'a> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V>(self, _: V) -> std::result::Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(de::Error::custom("only probing the shape"))
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> std::result::Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> std::result::Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_map<V>(self, _: V) -> std::result::Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        *self.0 = true;
+        Err(de::Error::custom("only probing the shape"))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        _: V,
+    ) -> std::result::Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        *self.0 = true;
+        Err(de::Error::custom("only probing the shape"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes byte_buf unit
+        unit_struct seq tuple tuple_struct enum identifier ignored_any
+    }
+}
+
+/// Checks that a key can be stored, and be part of a URL path.
+///
+/// Other characters, like `/`, `?` or spaces, are allowed, and percent-encoded in paths.
+pub(crate) fn validate_key(key: &str) -> Result<()> {
+    match key {
+        "" | "." | ".." => Err(Error::InvalidItemKey(key.to_string())),
+        _ => Ok(()),
+    }
+}
+
+/// Seconds since the Unix epoch, as used by the `__expires` attribute.
+pub(crate) fn timestamp(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|x| x.as_secs())
+        .unwrap_or_default()
 }
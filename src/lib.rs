@@ -1,38 +1,89 @@
-use std::fmt;
 use std::sync::Arc;
 
-use reqwest::{header, Client};
+use http::header;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
-pub use error::{Error, Result};
-pub use item::Item;
+pub use base::Base;
+pub use builder::DetaBuilder;
+#[cfg(feature = "derive")]
+pub use deta_derive::DetaItem;
+pub use drive::{Deleted, Drive, FilePage};
+pub use error::{Error, RequestError, Result};
+pub use item::{DetaItem, IntoItem, Item};
+pub use query::Query;
+pub use report::{FailedItem, PutManyReport};
+pub use retry::RetryPolicy;
+pub use transport::Transport;
+pub use typed_base::TypedBase;
 pub use update::Update;
 
+pub use http;
+#[cfg(feature = "reqwest")]
+pub use reqwest;
+
+mod base;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod builder;
+mod drive;
 mod error;
 mod item;
+mod query;
+mod report;
+mod retry;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod transport;
+mod typed_base;
 mod update;
 
 const URL: &str = "https://database.deta.sh/v1/";
+const DRIVE_URL: &str = "https://drive.deta.sh/v1/";
+
+/// The characters escaped in a segment of a URL path.
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'[')
+    .add(b'\\')
+    .add(b']')
+    .add(b'^')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}');
 
 /// The `Deta` client.
 ///
-/// This uses `reqwest::Client` internally. Create one and reuse it.
+/// This uses `reqwest::Client` internally, unless another [`Transport`](crate::Transport) is set.
+/// Create one and reuse it.
+/// Use [`Deta::builder`](Deta::builder) to configure it.
 ///
 /// You don't need to wrap it with a `Rc` or an `Arc`, because it uses an `Arc` internally.
 /// To reuse the client or pass it on to another thread, `.clone()` it.
 #[derive(Clone)]
 pub struct Deta {
-    client: Client,
+    transport: Arc<dyn Transport>,
     url: Arc<String>,
-    key: Arc<String>,
-    base_name: Option<Arc<String>>,
+    drive_url: Arc<String>,
+    key: header::HeaderValue,
+    retry: RetryPolicy,
 }
 
 impl Deta {
     /// Creates a new client.
     ///
     /// Use this if you have the `Project Key` in the env var `DETA_PROJECT_KEY`.
+    /// If the env var `DETA_BASE_ENDPOINT` is set, it's used as the
+    /// [`endpoint`](DetaBuilder::endpoint), e.g. to talk to a local `deta-emulator`.
     ///
     /// # Errors
     ///
@@ -53,7 +104,12 @@ impl Deta {
         use std::env;
 
         let key = env::var("DETA_PROJECT_KEY").map_err(|_| Error::KeyNotFound)?;
-        Self::key(key)
+        let builder = DetaBuilder::new(key);
+
+        match env::var("DETA_BASE_ENDPOINT") {
+            Ok(x) => builder.endpoint(x).build(),
+            Err(_) => builder.build(),
+        }
     }
 
     /// Creates a new client.
@@ -78,520 +134,234 @@ impl Deta {
     /// # }
     /// ```
     pub fn new_with_key(key: impl AsRef<str>) -> Result<Self> {
-        let key = key.as_ref().to_string();
-        Self::key(key)
+        DetaBuilder::new(key).build()
     }
 
-    fn key(key: String) -> Result<Self> {
-        let valid = !key.contains(|c: char| {
-            !(c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-' || c == '~')
-        });
-
-        if !valid {
-            return Err(Error::InvalidKey);
-        }
-
-        let pid = key.split('_').next().ok_or(Error::InvalidKey)?;
-
-        let mut headers = header::HeaderMap::new();
-        headers.insert(
-            header::HeaderName::from_static("x-api-key"),
-            header::HeaderValue::from_str(&key).map_err(|_| Error::InvalidKey)?,
-        );
-        headers.insert(
-            header::CONTENT_TYPE,
-            header::HeaderValue::from_static("application/json"),
-        );
-
-        let client = Client::builder()
-            .default_headers(headers)
-            .build()
-            .map_err(|_| Error::ClientInitError)?;
-        let url = Arc::new(format!("{}{}", URL, pid));
-        let key = Arc::new(key);
-
-        Ok(Self {
-            client,
-            url,
-            key,
-            base_name: None,
-        })
-    }
-
-    /// Sets the name of the database for the client.
-    ///
-    /// This internally clones the client and sets the name of the database.
+    /// Creates a builder to configure the client.
     ///
     /// # Arguments
     ///
-    /// * `base_name`: The name of the database.
+    /// * `key`: The `Project Key`.
     ///
     /// # Examples
     ///
     /// ```
     /// use deta::Deta;
+    /// use std::time::Duration;
     /// # fn main() -> deta::Result<()> {
-    /// let deta = Deta::new()?;
-    ///
-    /// let db_main = deta.base("main");
-    /// let db_test = deta.base("test");
+    /// let key = "a0abcdef_thisisaverysecretkey";
+    /// let deta = Deta::builder(key)
+    ///     .timeout(Duration::from_secs(10))
+    ///     .build()?;
     /// # Ok(())
     /// # }
     /// ```
-    pub fn base(&self, base_name: impl AsRef<str>) -> Self {
-        Self {
-            base_name: Some(Arc::new(base_name.as_ref().to_string())),
-            ..self.clone()
-        }
+    pub fn builder(key: impl AsRef<str>) -> DetaBuilder {
+        DetaBuilder::new(key)
     }
 
-    /// Get a stored item.
-    ///
-    /// # Arguments
+    /// Creates a client which keeps the items in memory instead of sending requests.
     ///
-    /// * `key`: The key (aka. ID) of the item you want to retrieve.
+    /// Only available with the `testing` feature.
     ///
-    /// # Errors
+    /// # Arguments
     ///
-    /// * [`Error::BaseNameNotPresent`](crate::Error::BaseNameNotPresent)
-    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
-    /// * [`Error::ItemNotFound`](crate::Error::ItemNotFound)
-    /// * [`Error::JSONDeserializingFailed`](crate::Error::JSONDeserializingFailed)
+    /// * `backend`: The in-memory backend. Clients made from clones of it share the same items.
     ///
     /// # Examples
     ///
     /// ```
-    /// use deta::{Deta, Item};
-    /// # #[tokio::main]
-    /// # async fn main() -> deta::Result<()> {
-    /// let deta = Deta::new()?;
+    /// use deta::testing::MemoryBackend;
+    /// use deta::Deta;
     ///
+    /// let deta = Deta::in_memory(MemoryBackend::new());
     /// let base = deta.base("main");
-    /// base.put(Item::new_with_key("get_id", 60)).await?;
-    /// let value: usize = base.get("get_id").await?;
-    ///
-    /// assert_eq!(value, 60);
-    /// # Ok(())
-    /// # }
     /// ```
-    pub async fn get<T>(&self, key: impl fmt::Display) -> Result<T>
-    where
-        T: DeserializeOwned,
-    {
-        let url = format!(
-            "{}/{}/items/{}",
-            self.url,
-            self.base_name.as_ref().ok_or(Error::BaseNameNotPresent)?,
-            key
-        );
-
-        let mut value: serde_json::Value = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|_| Error::RequestSendError)?
-            .error_for_status()
-            .map_err(|_| Error::ItemNotFound)?
-            .json()
-            .await
-            .map_err(|_| Error::JSONDeserializingFailed)?;
-
-        let len = value
-            .as_object()
-            .ok_or(Error::JSONDeserializingFailed)?
-            .len();
-
-        if len == 2 {
-            serde_json::from_value(value["value"].take())
-                .map_err(|_| Error::JSONDeserializingFailed)
-        } else {
-            value
-                .as_object_mut()
-                .ok_or(Error::JSONDeserializingFailed)?
-                .remove("key")
-                .ok_or(Error::JSONDeserializingFailed)?;
-            serde_json::from_value(value).map_err(|_| Error::JSONDeserializingFailed)
+    #[cfg(feature = "testing")]
+    pub fn in_memory(backend: testing::MemoryBackend) -> Self {
+        Self {
+            transport: Arc::new(backend),
+            url: Arc::new(testing::BASE_URL.to_string()),
+            drive_url: Arc::new(testing::DRIVE_URL.to_string()),
+            key: header::HeaderValue::from_static(""),
+            retry: RetryPolicy::default(),
         }
     }
 
-    /// Delete a stored item.
+    /// Creates a client which records its requests and their responses to a [`Cassette`](crate::testing::Cassette).
+    ///
+    /// Only available with the `testing` feature.
+    /// The requests are still sent with the transport of this client.
     ///
     /// # Arguments
     ///
-    /// * `key`: The key (aka. ID) of the item you want to delete.
+    /// * `path`: The JSON file of the cassette, which is overwritten.
     ///
     /// # Errors
     ///
-    /// * [`Error::BaseNameNotPresent`](crate::Error::BaseNameNotPresent)
-    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
+    /// Fails if the file can't be written.
     ///
     /// # Examples
     ///
     /// ```
-    /// use deta::{Deta, Item, Result};
-    /// # #[tokio::main]
-    /// # async fn main() -> Result<()> {
-    /// let deta = Deta::new()?;
-    ///
-    /// let base = deta.base("main");
-    /// base.put(Item::new_with_key("delete_id", 60)).await?;
-    /// base.delete("delete_id").await?;
-    ///
-    /// let item: Result<usize> = base.get("delete_id").await;
-    /// assert!(item.is_err());
+    /// use deta::Deta;
+    /// # fn main() -> anyhow::Result<()> {
+    /// let name = format!("deta_{}_recording.json", std::process::id());
+    /// let path = std::env::temp_dir().join(name);
+    /// let deta = Deta::new()?.recording(path)?;
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn delete(&self, key: impl fmt::Display) -> Result<()> {
-        let url = format!(
-            "{}/{}/items/{}",
-            self.url,
-            self.base_name.as_ref().ok_or(Error::BaseNameNotPresent)?,
-            key
-        );
-
-        self.client
-            .delete(&url)
-            .send()
-            .await
-            .map_err(|_| Error::RequestSendError)?;
+    #[cfg(feature = "testing")]
+    pub fn recording(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let cassette = testing::Cassette::record_with(path.as_ref(), self.transport.clone())?;
 
-        Ok(())
+        Ok(Self {
+            transport: Arc::new(cassette),
+            ..self.clone()
+        })
     }
 
-    /// Stores an item.
-    /// This request overwrites an item if the key already exists.
+    /// Creates a client which replays the responses recorded to a [`Cassette`](crate::testing::Cassette),
+    /// instead of sending requests.
     ///
-    /// Returns the key, if successful.
+    /// Only available with the `testing` feature.
+    /// It must have the project ID of the recording client, but not its `Project Key`.
     ///
     /// # Arguments
     ///
-    /// * `item`: An `Item`.
+    /// * `path`: The JSON file of the cassette.
     ///
     /// # Errors
     ///
-    /// * [`Error::BaseNameNotPresent`](crate::Error::BaseNameNotPresent)
-    /// * [`Error::JSONSerializingFailed`](crate::Error::JSONSerializingFailed)
-    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
-    /// * [`Error::BadRequest`](crate::Error::BadRequest)
-    /// * [`Error::JSONDeserializingFailed`](crate::Error::JSONDeserializingFailed)
+    /// Fails if the file can't be read, or isn't a cassette.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use deta::DetaBuilder;
+    /// # fn main() -> anyhow::Result<()> {
+    /// let deta = DetaBuilder::new("a0abcdef_replay")
+    ///     .build()?
+    ///     .replaying("tests/cassettes/consistency.json")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "testing")]
+    pub fn replaying(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        Ok(Self {
+            transport: Arc::new(testing::Cassette::replay(path)?),
+            ..self.clone()
+        })
+    }
+
+    /// Creates a handle to a Deta Base, which shares the `Project Key` and the connections of the client.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_name`: The name of the database.
     ///
     /// # Examples
     ///
     /// ```
-    /// use deta::{Deta, Item};
-    /// # #[tokio::main]
-    /// # async fn main() -> deta::Result<()> {
+    /// use deta::Deta;
+    /// # fn main() -> deta::Result<()> {
     /// let deta = Deta::new()?;
     ///
-    /// let base = deta.base("main");
-    /// # base.delete(5);
-    /// let item = Item::new_with_key("put_id", 5);
-    /// assert_eq!(base.put(item).await?, "put_id");
+    /// let db_main = deta.base("main");
+    /// let db_test = deta.base("test");
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn put<T>(&self, item: Item<T>) -> Result<String>
-    where
-        T: Serialize,
-    {
-        let url = format!(
-            "{}/{}/items",
-            self.url,
-            self.base_name.as_ref().ok_or(Error::BaseNameNotPresent)?,
-        );
-
-        let Item { key, value } = item;
-        let mut value = serde_json::to_value(value).map_err(|_| Error::JSONSerializingFailed)?;
-
-        if !value.is_object() {
-            value = serde_json::json!({ "value": value });
-        }
-
-        if let Some(x) = key {
-            value["key"] = serde_json::json!(x);
-        }
-
-        let req_body = serde_json::json!({ "items": [value] });
-
-        let value: serde_json::Value = self
-            .client
-            .put(&url)
-            .json(&req_body)
-            .send()
-            .await
-            .map_err(|_| Error::RequestSendError)?
-            .error_for_status()
-            .map_err(|_| Error::BadRequest)?
-            .json()
-            .await
-            .map_err(|_| Error::JSONDeserializingFailed)?;
-
-        let key = value["processed"]["items"]
-            .as_array()
-            .ok_or(Error::JSONDeserializingFailed)?[0]["key"]
-            .as_str()
-            .ok_or(Error::JSONDeserializingFailed)?
-            .to_string();
-
-        Ok(key)
+    pub fn base(&self, base_name: impl AsRef<str>) -> Base {
+        Base::new(self.clone(), base_name.as_ref().to_string())
     }
 
-    /// Stores multiple items in a single request.
-    /// This request overwrites an item if the key already exists.
-    ///
-    /// It returns a tuple of both processed and failed items.
+    /// Creates a handle to a Deta Base, which only stores items of type `T`.
     ///
     /// # Arguments
     ///
-    /// * `items`: A `Vec` of `Item`s.
-    ///
-    /// # Errors
-    ///
-    /// * [`Error::BaseNameNotPresent`](crate::Error::BaseNameNotPresent)
-    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
-    /// * [`Error::BadRequest`](crate::Error::BadRequest)
-    /// * [`Error::JSONDeserializingFailed`](crate::Error::JSONDeserializingFailed)
+    /// * `base_name`: The name of the database.
     ///
     /// # Examples
     ///
     /// ```
-    /// use deta::{Deta, Item};
-    /// # #[tokio::main]
-    /// # async fn main() -> deta::Result<()> {
+    /// use deta::Deta;
+    /// use std::collections::HashMap;
+    /// # fn main() -> deta::Result<()> {
     /// let deta = Deta::new()?;
     ///
-    /// let base = deta.base("main");
-    /// let numbers = ["zero", "one", "two", "three", "four"];
-    /// let vec = numbers
-    ///     .iter()
-    ///     .enumerate()
-    ///     .map(|(c, x)| Item::new_with_key(c, x))
-    ///     .collect::<Vec<_>>();
-    /// let (processed, failed): (Vec<Item<String>>, Vec<Item<String>>) = base.put_many(vec).await?;
+    /// let settings = deta.typed_base::<HashMap<String, String>>("settings");
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn put_many<T, U>(&self, items: Vec<Item<T>>) -> Result<(Vec<Item<U>>, Vec<Item<U>>)>
+    pub fn typed_base<T>(&self, base_name: impl AsRef<str>) -> TypedBase<T>
     where
-        T: Serialize,
-        U: DeserializeOwned,
+        T: Serialize + DeserializeOwned,
     {
-        if items.len() > 25 {
-            return Err(Error::VecTooLong);
-        }
-
-        let url = format!(
-            "{}/{}/items",
-            self.url,
-            self.base_name.as_ref().ok_or(Error::BaseNameNotPresent)?,
-        );
-
-        let items: Result<Vec<_>> = items
-            .into_iter()
-            .map(|x| {
-                let Item { key, value } = x;
-                let value = serde_json::to_value(value);
-
-                if let Ok(mut value) = value {
-                    if !value.is_object() {
-                        value = serde_json::json!({ "value": value });
-                    }
-
-                    if let Some(x) = key {
-                        value["key"] = serde_json::json!(x);
-                    }
-
-                    Ok(value)
-                } else {
-                    Err(Error::JSONSerializingFailed)
-                }
-            })
-            .collect();
-
-        let items = items?;
-
-        let req_body = serde_json::json!({ "items": items });
-
-        let PutResult { processed, failed }: PutResult<U> = self
-            .client
-            .put(&url)
-            .json(&req_body)
-            .send()
-            .await
-            .map_err(|_| Error::RequestSendError)?
-            .error_for_status()
-            .map_err(|_| Error::BadRequest)?
-            .json()
-            .await
-            .map_err(|_| Error::JSONDeserializingFailed)?;
-
-        let processed = processed.unwrap_or(Put { items: Vec::new() });
-        let failed = failed.unwrap_or(Put { items: Vec::new() });
-
-        let Put { items: processed } = processed;
-        let Put { items: failed } = failed;
-
-        Ok((processed, failed))
+        self.base(base_name).typed()
     }
 
-    /// Creates a new item only if no item with the same `key` exists.
+    /// Creates a handle to a Deta Drive, which shares the `Project Key` and the connections of the client.
     ///
-    /// Returns the key, if successful. If the same key exists returns an Error.
+    /// Clients created with [`in_memory`](Deta::in_memory) can't reach a Drive.
     ///
     /// # Arguments
     ///
-    /// * `item`: An `Item`.
-    ///
-    /// # Errors
-    ///
-    /// * [`Error::BaseNameNotPresent`](crate::Error::BaseNameNotPresent)
-    /// * [`Error::JSONSerializingFailed`](crate::Error::JSONSerializingFailed)
-    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
-    /// * [`Error::KeyConflict`](crate::Error::KeyConflict)
-    /// * [`Error::BadRequest`](crate::Error::BadRequest)
-    /// * [`Error::ServerError`](crate::Error::ServerError)
-    /// * [`Error::JSONDeserializingFailed`](crate::Error::JSONDeserializingFailed)
+    /// * `drive_name`: The name of the drive.
     ///
     /// # Examples
     ///
     /// ```
-    /// use deta::{Deta, Item};
-    /// # #[tokio::main]
-    /// # async fn main() -> deta::Result<()> {
+    /// use deta::Deta;
+    /// # fn main() -> deta::Result<()> {
     /// let deta = Deta::new()?;
     ///
-    /// let base = deta.base("main");
-    /// # base.delete("insert_id").await?;
-    /// let item = Item::new_with_key("insert_id", 60);
-    /// base.insert(item).await?;
+    /// let photos = deta.drive("photos");
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn insert<T>(&self, item: Item<T>) -> Result<String>
-    where
-        T: Serialize,
-    {
-        let url = format!(
-            "{}/{}/items",
-            self.url,
-            self.base_name.as_ref().ok_or(Error::BaseNameNotPresent)?,
-        );
-
-        let Item { key, value } = item;
-        let mut value = serde_json::to_value(value).map_err(|_| Error::JSONSerializingFailed)?;
-
-        if !value.is_object() {
-            value = serde_json::json!({ "value": value });
-        }
-
-        if let Some(x) = key {
-            value["key"] = serde_json::json!(x);
-        }
-
-        let req_body = serde_json::json!({ "item": value });
+    pub fn drive(&self, drive_name: impl AsRef<str>) -> Drive {
+        let url = format!("{}/{}", self.drive_url, encode(drive_name.as_ref()));
 
-        let json: serde_json::Value = self
-            .client
-            .post(&url)
-            .json(&req_body)
-            .send()
-            .await
-            .map_err(|_| Error::RequestSendError)?
-            .error_for_status()
-            .map_err(|e| {
-                if let Some(x) = e.status() {
-                    if x == reqwest::StatusCode::CONFLICT {
-                        return Error::KeyConflict;
-                    } else if x == reqwest::StatusCode::BAD_REQUEST {
-                        return Error::BadRequest;
-                    }
-                }
-                Error::ServerError
-            })?
-            .json()
-            .await
-            .map_err(|_| Error::JSONDeserializingFailed)?;
-
-        Ok(json["key"].as_str().ok_or(Error::ServerError)?.to_string())
+        Drive::new(self.transport.clone(), url, self.key.clone())
     }
 
-    /// Updates an item only if an item with `key` exists.
-    ///
-    /// # Arguments
+    /// Sets the policy for retrying failed requests.
     ///
-    /// * `update`: An `Update` struct.
+    /// This internally clones the client and sets the policy.
+    /// It applies to the bases created from the returned client.
     ///
-    /// # Errors
+    /// # Arguments
     ///
-    /// * [`Error::BaseNameNotPresent`](crate::Error::BaseNameNotPresent)
-    /// * [`Error::RequestSendError`](crate::Error::RequestSendError)
-    /// * [`Error::KeyNonexistent`](crate::Error::KeyNonexistent)
-    /// * [`Error::BadRequest`](crate::Error::BadRequest)
-    /// * [`Error::ServerError`](crate::Error::ServerError)
+    /// * `policy`: The [`RetryPolicy`](crate::RetryPolicy).
     ///
     /// # Examples
     ///
-    /// ```no_run
-    /// use deta::{Deta, Update};
-    /// # #[tokio::main]
-    /// # async fn main() -> deta::Result<()> {
+    /// ```
+    /// use deta::{Deta, RetryPolicy};
+    /// # fn main() -> deta::Result<()> {
     /// let deta = Deta::new()?;
     ///
-    /// let base = deta.base("main");
-    /// deta.update(
-    ///     "user-a",
-    ///     Update::new()
-    ///             .set("profile.age", 33)
-    ///         .set("profile.active", true)
-    ///         .set("profile.email", "jimmy@deta.sh")
-    ///         .increment("purchases", 2)
-    ///         .append("likes", "ramen")
-    ///         .prepend("likes", "noodles")
-    ///         .delete("profile.hometown")
-    ///         .delete("on_mobile"),
-    /// );
+    /// let deta = deta.with_retry_policy(RetryPolicy::never());
+    /// let db_main = deta.base("main");
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn update(&self, key: impl fmt::Display, update: Update) -> Result<()> {
-        let url = format!(
-            "{}/{}/items/{}",
-            self.url,
-            self.base_name.as_ref().ok_or(Error::BaseNameNotPresent)?,
-            key
-        );
-
-        self.client
-            .patch(&url)
-            .json(&update)
-            .send()
-            .await
-            .map_err(|_| Error::RequestSendError)?
-            .error_for_status()
-            .map_err(|e| {
-                if let Some(x) = e.status() {
-                    if x == reqwest::StatusCode::NOT_FOUND {
-                        return Error::KeyNonexistent;
-                    } else if x == reqwest::StatusCode::BAD_REQUEST {
-                        return Error::BadRequest;
-                    }
-                }
-                Error::ServerError
-            })?;
-
-        Ok(())
+    pub fn with_retry_policy(&self, policy: RetryPolicy) -> Self {
+        Self {
+            retry: policy,
+            ..self.clone()
+        }
     }
 }
 
-#[derive(Serialize, Deserialize)]
-struct Put<T> {
-    items: Vec<Item<T>>,
+/// Percent-encodes a segment of a URL path, like a base name or a key.
+pub(crate) fn encode(segment: &str) -> String {
+    utf8_percent_encode(segment, PATH_SEGMENT).to_string()
 }
 
 #[derive(Serialize, Deserialize)]
-struct PutResult<T> {
-    processed: Option<Put<T>>,
-    failed: Option<Put<T>>,
+pub(crate) struct ErrorResult {
+    errors: Vec<String>,
 }
@@ -0,0 +1,260 @@
+use std::string;
+
+use serde::ser::{SerializeSeq, Serializer};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Only used for query requests.
+///
+/// Conditions added one after another are combined with a logical AND.
+/// Calling [`or`](Query::or) starts a new group of conditions,
+/// and an item matches the query if it matches any of the groups.
+///
+/// # Examples
+///
+/// ```
+/// use deta::Query;
+///
+/// // (age < 30 AND name starts with "J") OR (hometown == "Berlin")
+/// let query = Query::new()
+///     .less_than("age", 30)
+///     .prefix("name", "J")
+///     .or()
+///     .equal("hometown", "Berlin");
+/// ```
+#[derive(Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Query {
+    groups: Vec<Map<String, Value>>,
+    #[serde(skip)]
+    limit: Option<usize>,
+}
+
+impl Query {
+    /// To initialize a new empty `Query` struct.
+    ///
+    /// An empty query matches every item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::Query;
+    /// let query = Query::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// To start a new group of conditions.
+    ///
+    /// The groups are combined with a logical OR.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::Query;
+    /// let query = Query::new()
+    ///     .equal("name", "Jimmy")
+    ///     .or()
+    ///     .equal("name", "Joe");
+    ///
+    /// assert_eq!(
+    ///     serde_json::to_value(&query).unwrap(),
+    ///     serde_json::json!([{ "name": "Jimmy" }, { "name": "Joe" }]),
+    /// );
+    /// ```
+    pub fn or(mut self) -> Self {
+        if self.groups.last().is_some_and(|x| !x.is_empty()) {
+            self.groups.push(Map::new());
+        }
+        self
+    }
+
+    /// To match items where an attribute is equal to a value.
+    ///
+    /// Nested attributes can be accessed with a `.`, like `profile.age`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::Query;
+    /// let query = Query::new().equal("profile.active", true);
+    /// ```
+    pub fn equal(self, key: impl string::ToString, value: impl Into<Value>) -> Self {
+        self.condition(key.to_string(), value.into())
+    }
+
+    /// To match items where an attribute is not equal to a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::Query;
+    /// let query = Query::new().not_equal("name", "Jimmy");
+    /// ```
+    pub fn not_equal(self, key: impl string::ToString, value: impl Into<Value>) -> Self {
+        self.operator(key, "ne", value.into())
+    }
+
+    /// To match items where an attribute is less than a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::Query;
+    /// let query = Query::new().less_than("age", 30);
+    /// ```
+    pub fn less_than(self, key: impl string::ToString, value: impl Into<Value>) -> Self {
+        self.operator(key, "lt", value.into())
+    }
+
+    /// To match items where an attribute is greater than a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::Query;
+    /// let query = Query::new().greater_than("age", 30);
+    /// ```
+    pub fn greater_than(self, key: impl string::ToString, value: impl Into<Value>) -> Self {
+        self.operator(key, "gt", value.into())
+    }
+
+    /// To match items where an attribute is less than or equal to a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::Query;
+    /// let query = Query::new().less_than_or_equal("age", 30);
+    /// ```
+    pub fn less_than_or_equal(self, key: impl string::ToString, value: impl Into<Value>) -> Self {
+        self.operator(key, "lte", value.into())
+    }
+
+    /// To match items where an attribute is greater than or equal to a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::Query;
+    /// let query = Query::new().greater_than_or_equal("age", 30);
+    /// ```
+    pub fn greater_than_or_equal(
+        self,
+        key: impl string::ToString,
+        value: impl Into<Value>,
+    ) -> Self {
+        self.operator(key, "gte", value.into())
+    }
+
+    /// To match items where an attribute starts with a prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::Query;
+    /// let query = Query::new().prefix("name", "Ji");
+    /// ```
+    pub fn prefix(self, key: impl string::ToString, value: impl string::ToString) -> Self {
+        self.operator(key, "pfx", Value::String(value.to_string()))
+    }
+
+    /// To match items where an attribute lies in a range, both ends included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::Query;
+    /// let query = Query::new().range("age", 18, 30);
+    ///
+    /// assert_eq!(
+    ///     serde_json::to_value(&query).unwrap(),
+    ///     serde_json::json!([{ "age?r": [18, 30] }]),
+    /// );
+    /// ```
+    pub fn range(
+        self,
+        key: impl string::ToString,
+        start: impl Into<Value>,
+        end: impl Into<Value>,
+    ) -> Self {
+        self.operator(key, "r", Value::Array(vec![start.into(), end.into()]))
+    }
+
+    /// To match items where an attribute contains a value.
+    ///
+    /// Works for substrings of a string and for elements of a list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::Query;
+    /// let query = Query::new().contains("likes", "ramen");
+    /// ```
+    pub fn contains(self, key: impl string::ToString, value: impl Into<Value>) -> Self {
+        self.operator(key, "contains", value.into())
+    }
+
+    /// To match items where an attribute doesn't contain a value.
+    ///
+    /// Works for substrings of a string and for elements of a list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::Query;
+    /// let query = Query::new().not_contains("likes", "ramen");
+    /// ```
+    pub fn not_contains(self, key: impl string::ToString, value: impl Into<Value>) -> Self {
+        self.operator(key, "not_contains", value.into())
+    }
+
+    /// To set the maximum number of items fetched in a single request.
+    ///
+    /// This only sets the size of a page, all the matching items are still fetched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::Query;
+    /// let query = Query::new().equal("name", "Jimmy").limit(100);
+    /// ```
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub(crate) fn page_limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    fn operator(self, key: impl string::ToString, operator: &str, value: Value) -> Self {
+        self.condition(format!("{}?{}", key.to_string(), operator), value)
+    }
+
+    fn condition(mut self, key: String, value: Value) -> Self {
+        if self.groups.is_empty() {
+            self.groups.push(Map::new());
+        }
+
+        if let Some(x) = self.groups.last_mut() {
+            x.insert(key, value);
+        }
+        self
+    }
+}
+
+impl Serialize for Query {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let groups = self.groups.iter().filter(|x| !x.is_empty());
+        let mut seq = serializer.serialize_seq(None)?;
+        for group in groups {
+            seq.serialize_element(group)?;
+        }
+        seq.end()
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{Error, Result};
+
+/// The outcome of storing multiple items.
+///
+/// Returned by [`Base::put_many`](crate::Base::put_many) and [`Base::put_all`](crate::Base::put_all).
+/// The Deta Base may store some of the items and report the others as failed,
+/// so check [`failed`](PutManyReport::failed) even if the request succeeded.
+/// [`Base::put_all`](crate::Base::put_all) also keeps the errors of the batches it couldn't send
+/// in [`errors`](PutManyReport::errors).
+///
+/// # Examples
+///
+/// ```
+/// use deta::{Deta, Item};
+/// # #[tokio::main]
+/// # async fn main() -> deta::Result<()> {
+/// let deta = Deta::new()?;
+///
+/// let base = deta.base("main");
+/// let items = vec![
+///     Item::new_with_key("report_a", 1),
+///     Item::new_with_key("report_b", 2),
+/// ];
+/// let report = base.put_many(items).await?;
+///
+/// for failed in report.failed() {
+///     println!("{:?} failed: {:?}", failed.item.key, failed.reason);
+/// }
+/// assert_eq!(report.processed(), ["report_a", "report_b"]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct PutManyReport<T> {
+    processed: Vec<String>,
+    failed: Vec<FailedItem<T>>,
+    unmatched: Vec<serde_json::Value>,
+    errors: Vec<Error>,
+}
+
+/// An item which the Deta Base didn't store.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailedItem<T> {
+    /// The item, as it was given.
+    pub item: T,
+    /// The reason sent by the Deta Base, if any.
+    ///
+    /// The Deta Base doesn't send a reason for each failed item,
+    /// so this is only set if the response has an `errors` list, and is usually `None`.
+    pub reason: Option<String>,
+}
+
+impl<T> PutManyReport<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            processed: Vec::new(),
+            failed: Vec::new(),
+            unmatched: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// The keys of the stored items.
+    ///
+    /// Keys generated by the Deta Base are included.
+    pub fn processed(&self) -> &[String] {
+        &self.processed
+    }
+
+    /// The items which weren't stored.
+    pub fn failed(&self) -> &[FailedItem<T>] {
+        &self.failed
+    }
+
+    /// The items the Deta Base reported as failed, which didn't match any of the items given.
+    ///
+    /// These are the items as sent back by the Deta Base.
+    /// This only happens if the Deta Base changed the items before sending them back,
+    /// so the items they were made of are missing from both [`processed`](PutManyReport::processed)
+    /// and [`failed`](PutManyReport::failed).
+    pub fn unmatched(&self) -> &[serde_json::Value] {
+        &self.unmatched
+    }
+
+    /// The errors of the batches which couldn't be stored by [`Base::put_all`](crate::Base::put_all).
+    ///
+    /// The items of those batches are in [`failed`](PutManyReport::failed).
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+
+    /// Whether every item was stored.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty() && self.unmatched.is_empty() && self.errors.is_empty()
+    }
+
+    /// Takes the parts of the report, in the order of their accessors:
+    /// the keys of the stored items, the items which weren't stored,
+    /// the [`unmatched`](PutManyReport::unmatched) items and the [`errors`](PutManyReport::errors).
+    pub fn into_parts(
+        self,
+    ) -> (
+        Vec<String>,
+        Vec<FailedItem<T>>,
+        Vec<serde_json::Value>,
+        Vec<Error>,
+    ) {
+        (self.processed, self.failed, self.unmatched, self.errors)
+    }
+
+    /// Takes the items which weren't stored, to store them again.
+    pub fn into_failed_items(self) -> Vec<T> {
+        self.failed.into_iter().map(|x| x.item).collect()
+    }
+
+    pub(crate) fn push_processed(&mut self, key: String) {
+        self.processed.push(key);
+    }
+
+    pub(crate) fn push_failed(&mut self, item: T, reason: Option<String>) {
+        self.failed.push(FailedItem { item, reason });
+    }
+
+    pub(crate) fn push_unmatched(&mut self, item: serde_json::Value) {
+        self.unmatched.push(item);
+    }
+
+    pub(crate) fn push_error(&mut self, error: Error) {
+        self.errors.push(error);
+    }
+
+    /// Fails with the first error, for the requests which only send a single batch.
+    pub(crate) fn into_result(mut self) -> Result<Self> {
+        if self.errors.is_empty() {
+            Ok(self)
+        } else {
+            Err(self.errors.swap_remove(0))
+        }
+    }
+
+    /// Adds the outcome of another batch.
+    pub(crate) fn merge(&mut self, other: Self) {
+        self.processed.extend(other.processed);
+        self.failed.extend(other.failed);
+        self.unmatched.extend(other.unmatched);
+        self.errors.extend(other.errors);
+    }
+}
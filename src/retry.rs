@@ -0,0 +1,184 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, SystemTime};
+
+use http::{header, StatusCode};
+
+/// When and how often failed requests are sent again.
+///
+/// Requests are retried if they couldn't be sent,
+/// or if the server responded with `429 Too Many Requests` or a `5xx` status code.
+/// The delay doubles with every attempt, starting at the base delay, up to the max delay.
+///
+/// Only the requests which can be safely repeated are retried by default,
+/// which are the requests of `get`, `put`, `put_many`, `put_all`, `delete` and `query`.
+/// `insert` and `update` are only retried if
+/// [`retry_non_idempotent`](RetryPolicy::retry_non_idempotent) is set.
+///
+/// The default policy makes at most 3 attempts, with delays between 100ms and 10s, and jitter.
+///
+/// # Examples
+///
+/// ```
+/// use deta::{DetaBuilder, RetryPolicy};
+/// use std::time::Duration;
+/// # fn main() -> deta::Result<()> {
+/// let policy = RetryPolicy::new()
+///     .max_attempts(5)
+///     .base_delay(Duration::from_millis(200))
+///     .max_delay(Duration::from_secs(5));
+///
+/// let deta = DetaBuilder::new("a0abcdef_thisisaverysecretkey")
+///     .retry_policy(policy)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+    non_idempotent: bool,
+    failed_items: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+            jitter: true,
+            non_idempotent: false,
+            failed_items: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates the default policy.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a policy which never retries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::{DetaBuilder, RetryPolicy};
+    /// let builder = DetaBuilder::new("a0abcdef_thisisaverysecretkey")
+    ///     .retry_policy(RetryPolicy::never());
+    /// ```
+    pub fn never() -> Self {
+        Self::default().max_attempts(1)
+    }
+
+    /// Sets the maximum number of attempts, including the first one.
+    ///
+    /// A value of `0` is treated as `1`.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Sets the delay before the first retry.
+    pub fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// Sets the maximum delay between two attempts.
+    ///
+    /// If the server asks to wait longer with a `Retry-After` header, the request isn't retried.
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Sets whether the delays are randomized, between zero and the computed delay.
+    ///
+    /// This keeps many clients from retrying at the same moment.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Sets whether `insert` and `update` are retried too.
+    ///
+    /// Their requests may have been applied even if they failed,
+    /// so a retried `insert` may fail with a conflict,
+    /// and a retried `update` may increment or append twice.
+    pub fn retry_non_idempotent(mut self, retry: bool) -> Self {
+        self.non_idempotent = retry;
+        self
+    }
+
+    /// Sets whether the items which `put_many` and `put_all` couldn't store are sent again.
+    ///
+    /// Only the failed items are sent again, with the same delays as failed requests.
+    /// The items still failing after the last attempt are reported as failed.
+    pub fn retry_failed_items(mut self, retry: bool) -> Self {
+        self.failed_items = retry;
+        self
+    }
+
+    pub(crate) fn retries_failed_items(&self) -> bool {
+        self.failed_items
+    }
+
+    /// The delay before the next attempt, if the request should be retried.
+    ///
+    /// `attempt` is the number of attempts made so far.
+    pub(crate) fn delay(
+        &self,
+        attempt: u32,
+        idempotent: bool,
+        retry_after: Option<Duration>,
+    ) -> Option<Duration> {
+        if attempt >= self.max_attempts || !(idempotent || self.non_idempotent) {
+            return None;
+        }
+
+        let factor = 2u32.saturating_pow(attempt - 1);
+        let delay = self
+            .base_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+
+        let delay = if self.jitter {
+            let random = RandomState::new().build_hasher().finish();
+            delay.mul_f64(random as f64 / u64::MAX as f64)
+        } else {
+            delay
+        };
+
+        match retry_after {
+            Some(x) if x > self.max_delay => None,
+            Some(x) => Some(delay.max(x)),
+            None => Some(delay),
+        }
+    }
+}
+
+/// Whether a response with this status code should be retried.
+pub(crate) fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Parses a `Retry-After` header, given either in seconds or as an HTTP date.
+pub(crate) fn retry_after(headers: &header::HeaderMap) -> Option<Duration> {
+    let value = headers.get(header::RETRY_AFTER)?.to_str().ok()?.trim();
+
+    match value.parse() {
+        Ok(x) => Some(Duration::from_secs(x)),
+        Err(_) => httpdate::parse_http_date(value)
+            .ok()?
+            .duration_since(SystemTime::now())
+            .ok()
+            .or(Some(Duration::from_secs(0))),
+    }
+}
@@ -0,0 +1,685 @@
+//! Helpers to test code using this crate without a Deta project.
+//!
+//! Only available with the `testing` feature.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use bytes::Bytes;
+use futures::future::{self, BoxFuture};
+use http::{header, Method, Request, Response, StatusCode};
+use percent_encoding::percent_decode_str;
+use serde_json::{json, Map, Value};
+
+use crate::item;
+use crate::transport::{Body, Transport, TransportError};
+
+pub use cassette::Cassette;
+pub(crate) use cassette::Unrecorded;
+
+mod cassette;
+
+/// The maximum number of items in a single put request.
+const MAX_ITEMS: usize = 25;
+/// The maximum size of a single item.
+const MAX_ITEM_SIZE: usize = 400 * 1024;
+/// The number of items in a page of query results, if no limit is given.
+const DEFAULT_LIMIT: usize = 1000;
+
+/// The URL of the bases of a client using a `MemoryBackend`.
+pub(crate) const BASE_URL: &str = "memory://base";
+/// The URL of the drives of a client using a `MemoryBackend`, which it can't reach.
+pub(crate) const DRIVE_URL: &str = "memory://drive";
+
+/// An in-memory stand-in for the Deta Base API.
+///
+/// It handles the same requests as the Deta Base API and responds the same way,
+/// including the conflicts of inserts, the update operations, item expiry and query filters.
+/// Every base of the project is kept in memory, and is lost when the last clone is dropped.
+///
+/// Clones share the same items, so keep a clone around to control the clock.
+/// It's the [`Transport`](crate::Transport) of the clients created with
+/// [`Deta::in_memory`](crate::Deta::in_memory).
+///
+/// # Examples
+///
+/// ```
+/// use deta::testing::MemoryBackend;
+/// use deta::{Deta, Item};
+/// # #[tokio::main]
+/// # async fn main() -> deta::Result<()> {
+/// let backend = MemoryBackend::new();
+/// let deta = Deta::in_memory(backend.clone());
+///
+/// let base = deta.base("main");
+/// base.put(Item::new_with_key("id", 5)).await?;
+/// let value: usize = base.get("id").await?;
+///
+/// assert_eq!(value, 5);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MemoryBackend {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    bases: HashMap<String, BTreeMap<String, Value>>,
+    keys: u64,
+    offset: Duration,
+    failures: VecDeque<StatusCode>,
+    rejections: usize,
+}
+
+impl MemoryBackend {
+    /// Creates a new backend without any items.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Moves the clock used for item expiry forward.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::testing::MemoryBackend;
+    /// use deta::{Deta, Item, Result};
+    /// use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// let backend = MemoryBackend::new();
+    /// let base = Deta::in_memory(backend.clone()).base("main");
+    ///
+    /// let item = Item::new_with_key("session", 5).expire_in(Duration::from_secs(60));
+    /// base.put(item).await?;
+    /// backend.advance(Duration::from_secs(120));
+    ///
+    /// let value: Result<usize> = base.get("session").await;
+    /// assert!(value.is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn advance(&self, duration: Duration) {
+        self.lock().offset += duration;
+    }
+
+    /// Makes the next `count` requests fail with `status`, without handling them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::http::StatusCode;
+    /// use deta::testing::MemoryBackend;
+    /// use deta::{Deta, Item, RetryPolicy};
+    /// # #[tokio::main]
+    /// # async fn main() -> deta::Result<()> {
+    /// let backend = MemoryBackend::new();
+    /// let base = Deta::in_memory(backend.clone())
+    ///     .with_retry_policy(RetryPolicy::never())
+    ///     .base("main");
+    ///
+    /// backend.fail_next(1, StatusCode::SERVICE_UNAVAILABLE);
+    /// assert!(base.put(Item::new_with_key("id", 5)).await.is_err());
+    /// assert!(base.put(Item::new_with_key("id", 5)).await.is_ok());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn fail_next(&self, count: usize, status: StatusCode) {
+        self.lock()
+            .failures
+            .extend(std::iter::repeat_n(status, count));
+    }
+
+    /// Makes the next `count` items of put requests be reported as failed, without storing them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::testing::MemoryBackend;
+    /// use deta::{Deta, Error, Item};
+    /// # #[tokio::main]
+    /// # async fn main() -> deta::Result<()> {
+    /// let backend = MemoryBackend::new();
+    /// let base = Deta::in_memory(backend.clone()).base("main");
+    ///
+    /// backend.reject_next(1);
+    /// let result = base.put(Item::new_with_key("id", 5)).await;
+    /// assert!(matches!(result, Err(Error::ItemRejected(_))));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reject_next(&self, count: usize) {
+        self.lock().rejections += count;
+    }
+
+    /// Removes every item of every base.
+    pub fn clear(&self) {
+        self.lock().bases.clear();
+    }
+
+    /// Exports every item of every base, as an object of bases mapping keys to items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::http::Method;
+    /// use deta::testing::MemoryBackend;
+    /// use serde_json::json;
+    ///
+    /// let backend = MemoryBackend::new();
+    /// let body = json!({ "item": { "key": "id", "value": 5 } });
+    /// backend.handle(&Method::POST, "main/items", Some(body));
+    ///
+    /// let items = backend.export();
+    /// assert_eq!(items, json!({ "main": { "id": { "key": "id", "value": 5 } } }));
+    ///
+    /// let backend = MemoryBackend::import(items).unwrap();
+    /// ```
+    pub fn export(&self) -> Value {
+        json!(self.lock().bases)
+    }
+
+    /// Creates a new backend with items exported by [`export`](MemoryBackend::export).
+    ///
+    /// # Errors
+    ///
+    /// Fails if `items` isn't an object of bases mapping keys to items.
+    pub fn import(items: Value) -> serde_json::Result<Self> {
+        let bases = serde_json::from_value(items)?;
+        let state = State {
+            bases,
+            ..State::default()
+        };
+
+        Ok(Self {
+            state: Arc::new(Mutex::new(state)),
+        })
+    }
+
+    /// Handles a request to the Deta Base API.
+    ///
+    /// Returns the status code and the body of the response.
+    ///
+    /// # Arguments
+    ///
+    /// * `method`: The method of the request.
+    /// * `path`: The path of the request after the project ID, like `main/items/some_key`.
+    /// * `body`: The JSON body of the request, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::http::{Method, StatusCode};
+    /// use deta::testing::MemoryBackend;
+    /// use serde_json::json;
+    ///
+    /// let backend = MemoryBackend::new();
+    /// let body = json!({ "item": { "key": "id", "value": 5 } });
+    /// let (status, _) = backend.handle(&Method::POST, "main/items", Some(body));
+    /// assert_eq!(status, StatusCode::CREATED);
+    ///
+    /// let (status, item) = backend.handle(&Method::GET, "main/items/id", None);
+    /// assert_eq!(status, StatusCode::OK);
+    /// assert_eq!(item, json!({ "key": "id", "value": 5 }));
+    /// ```
+    pub fn handle(&self, method: &Method, path: &str, body: Option<Value>) -> (StatusCode, Value) {
+        let mut parts = path.trim_matches('/').splitn(3, '/');
+        let base = parts.next().map(decode).unwrap_or_default();
+        let resource = parts.next();
+        let key = parts.next().map(decode);
+
+        let mut state = self.lock();
+
+        if let Some(status) = state.failures.pop_front() {
+            let message = status.canonical_reason().unwrap_or("Failure");
+            return error(status, message);
+        }
+
+        let now = item::timestamp(SystemTime::now() + state.offset);
+        let state = &mut *state;
+        let base = state.bases.entry(base).or_default();
+        base.retain(|_, x| !is_expired(x, now));
+
+        let body = body.unwrap_or(Value::Null);
+
+        match (method, resource, key) {
+            (&Method::PUT, Some("items"), None) => {
+                put(base, &mut state.keys, &mut state.rejections, body)
+            }
+            (&Method::POST, Some("items"), None) => insert(base, &mut state.keys, body),
+            (&Method::GET, Some("items"), Some(key)) => get(base, key),
+            (&Method::DELETE, Some("items"), Some(key)) => delete(base, key),
+            (&Method::PATCH, Some("items"), Some(key)) => update(base, key, body),
+            (&Method::POST, Some("query"), None) => query(base, body),
+            _ => error(StatusCode::NOT_FOUND, "Not found"),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Transport for MemoryBackend {
+    fn send(
+        &self,
+        request: Request<Bytes>,
+    ) -> BoxFuture<'static, Result<Response<Body>, TransportError>> {
+        let (parts, body) = request.into_parts();
+
+        if parts.uri.host() != Some("base") {
+            let e = "the memory backend can only handle requests to a Deta Base";
+            return Box::pin(future::ready(Err(e.into())));
+        }
+
+        let body = if body.is_empty() {
+            None
+        } else {
+            match serde_json::from_slice(&body) {
+                Ok(x) => Some(x),
+                Err(e) => return Box::pin(future::ready(Err(e.into()))),
+            }
+        };
+
+        let (status, value) = self.handle(&parts.method, parts.uri.path(), body);
+        let response = Response::builder()
+            .status(status)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(value.to_string().into_bytes()))
+            .map_err(Into::into);
+
+        Box::pin(future::ready(response))
+    }
+}
+
+fn put(
+    base: &mut BTreeMap<String, Value>,
+    keys: &mut u64,
+    rejections: &mut usize,
+    body: Value,
+) -> (StatusCode, Value) {
+    let items = match body.get("items").and_then(Value::as_array) {
+        Some(x) => x,
+        None => return error(StatusCode::BAD_REQUEST, "Bad request"),
+    };
+
+    if items.len() > MAX_ITEMS {
+        return error(StatusCode::BAD_REQUEST, "Number of items exceeds 25");
+    }
+
+    let mut processed = Vec::with_capacity(items.len());
+    for x in items {
+        let item = match prepare(x, keys, base) {
+            Ok(x) => x,
+            Err(e) => return e,
+        };
+
+        let key = item["key"].as_str().unwrap_or_default();
+        if processed.iter().any(|x: &Value| x["key"] == key) {
+            return error(StatusCode::BAD_REQUEST, "Duplicate keys in items");
+        }
+
+        processed.push(item);
+    }
+
+    let rejected = processed.len().min(*rejections);
+    *rejections -= rejected;
+    let failed = processed.split_off(processed.len() - rejected);
+
+    for x in &processed {
+        let key = x["key"].as_str().unwrap_or_default().to_string();
+        base.insert(key, x.clone());
+    }
+
+    let mut result = json!({ "processed": { "items": processed } });
+    if !failed.is_empty() {
+        result["failed"] = json!({ "items": failed });
+    }
+
+    (StatusCode::MULTI_STATUS, result)
+}
+
+fn insert(base: &mut BTreeMap<String, Value>, keys: &mut u64, body: Value) -> (StatusCode, Value) {
+    let item = match body.get("item") {
+        Some(x) => x,
+        None => return error(StatusCode::BAD_REQUEST, "Bad request"),
+    };
+
+    let item = match prepare(item, keys, base) {
+        Ok(x) => x,
+        Err(e) => return e,
+    };
+
+    let key = item["key"].as_str().unwrap_or_default().to_string();
+    if base.contains_key(&key) {
+        return error(StatusCode::CONFLICT, "Key already exists");
+    }
+
+    base.insert(key, item.clone());
+    (StatusCode::CREATED, item)
+}
+
+fn get(base: &BTreeMap<String, Value>, key: String) -> (StatusCode, Value) {
+    match base.get(&key) {
+        Some(x) => (StatusCode::OK, x.clone()),
+        None => (StatusCode::NOT_FOUND, json!({ "key": key })),
+    }
+}
+
+fn delete(base: &mut BTreeMap<String, Value>, key: String) -> (StatusCode, Value) {
+    base.remove(&key);
+    (StatusCode::OK, json!({ "key": key }))
+}
+
+fn update(base: &mut BTreeMap<String, Value>, key: String, body: Value) -> (StatusCode, Value) {
+    let item = match base.get(&key) {
+        Some(x) => x,
+        None => return error(StatusCode::NOT_FOUND, "Key not found"),
+    };
+
+    let operations = ["set", "increment", "append", "prepend"];
+    let touches_key = operations
+        .iter()
+        .filter_map(|x| body.get(x).and_then(Value::as_object))
+        .any(|x| x.contains_key("key"))
+        || body["delete"]
+            .as_array()
+            .is_some_and(|x| x.iter().any(|x| x == "key"));
+    if touches_key {
+        return error(StatusCode::BAD_REQUEST, "Key cannot be updated");
+    }
+
+    let mut item = item.clone();
+
+    for (path, value) in object(&body, "set") {
+        set_path(&mut item, path, value.clone());
+    }
+
+    for (path, value) in object(&body, "increment") {
+        let current = get_path(&item, path).cloned().unwrap_or_else(|| json!(0));
+        let value = match add(&current, value) {
+            Some(x) => x,
+            None => return error(StatusCode::BAD_REQUEST, "Increment on a non-number"),
+        };
+        set_path(&mut item, path, value);
+    }
+
+    for (path, value) in object(&body, "append") {
+        let mut list = match list(&item, path) {
+            Ok(x) => x,
+            Err(e) => return e,
+        };
+        list.extend(values(value));
+        set_path(&mut item, path, Value::Array(list));
+    }
+
+    for (path, value) in object(&body, "prepend") {
+        let list = match list(&item, path) {
+            Ok(x) => x,
+            Err(e) => return e,
+        };
+        let mut value = values(value);
+        value.extend(list);
+        set_path(&mut item, path, Value::Array(value));
+    }
+
+    for path in body["delete"].as_array().into_iter().flatten() {
+        if let Some(x) = path.as_str() {
+            remove_path(&mut item, x);
+        }
+    }
+
+    base.insert(key.clone(), item);
+
+    let mut response = body;
+    if let Some(x) = response.as_object_mut() {
+        x.insert("key".to_string(), json!(key));
+    }
+    (StatusCode::OK, response)
+}
+
+fn query(base: &BTreeMap<String, Value>, body: Value) -> (StatusCode, Value) {
+    let groups = match &body["query"] {
+        Value::Null => Vec::new(),
+        Value::Array(x) => x.clone(),
+        _ => return error(StatusCode::BAD_REQUEST, "Bad query"),
+    };
+    let limit = body["limit"].as_u64().map_or(DEFAULT_LIMIT, |x| x as usize);
+    let last = body["last"].as_str();
+
+    let mut items = base
+        .iter()
+        .filter(|(key, _)| last.is_none_or(|x| key.as_str() > x))
+        .filter(|(_, item)| groups.is_empty() || groups.iter().any(|x| matches(item, x)))
+        .map(|(_, item)| item.clone());
+
+    let page = items.by_ref().take(limit).collect::<Vec<_>>();
+    let mut paging = json!({ "size": page.len() });
+    if items.next().is_some() {
+        if let Some(x) = page.last() {
+            paging["last"] = x["key"].clone();
+        }
+    }
+
+    (StatusCode::OK, json!({ "paging": paging, "items": page }))
+}
+
+/// Checks an item to be stored, and gives it a key if it doesn't have one.
+fn prepare(
+    item: &Value,
+    keys: &mut u64,
+    base: &BTreeMap<String, Value>,
+) -> Result<Value, (StatusCode, Value)> {
+    let mut item = item.clone();
+    let object = match item.as_object_mut() {
+        Some(x) => x,
+        None => return Err(error(StatusCode::BAD_REQUEST, "Item must be an object")),
+    };
+
+    match object.get("key") {
+        Some(Value::String(x)) if !x.is_empty() => {}
+        None => {
+            let key = loop {
+                *keys += 1;
+                let key = generate_key(*keys);
+                if !base.contains_key(&key) {
+                    break key;
+                }
+            };
+            object.insert("key".to_string(), json!(key));
+        }
+        Some(_) => return Err(error(StatusCode::BAD_REQUEST, "Key must be a string")),
+    }
+
+    let size = serde_json::to_vec(&item).map_or(0, |x| x.len());
+    if size > MAX_ITEM_SIZE {
+        return Err(error(StatusCode::BAD_REQUEST, "Item size exceeds 400KB"));
+    }
+
+    Ok(item)
+}
+
+/// Makes a key which looks like the ones generated by Deta.
+fn generate_key(seed: u64) -> String {
+    const CHARS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+    // splitmix64, so that keys are spread out but deterministic.
+    let mut x = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^= x >> 31;
+
+    (0..12)
+        .map(|_| {
+            let c = CHARS[(x % CHARS.len() as u64) as usize] as char;
+            x /= CHARS.len() as u64;
+            c
+        })
+        .collect()
+}
+
+fn is_expired(item: &Value, now: u64) -> bool {
+    item["__expires"].as_f64().is_some_and(|x| x <= now as f64)
+}
+
+/// Whether an item matches all the conditions of a query group.
+fn matches(item: &Value, group: &Value) -> bool {
+    let group = match group.as_object() {
+        Some(x) => x,
+        None => return false,
+    };
+
+    group.iter().all(|(condition, value)| {
+        let (path, operator) = match condition.rfind('?') {
+            Some(i) => (&condition[..i], &condition[i + 1..]),
+            None => (condition.as_str(), ""),
+        };
+        let attribute = get_path(item, path);
+
+        match operator {
+            "" => attribute.is_some_and(|x| equal(x, value)),
+            "ne" => !attribute.is_some_and(|x| equal(x, value)),
+            "lt" => compare(attribute, value) == Some(Ordering::Less),
+            "gt" => compare(attribute, value) == Some(Ordering::Greater),
+            "lte" => matches!(
+                compare(attribute, value),
+                Some(Ordering::Less) | Some(Ordering::Equal)
+            ),
+            "gte" => matches!(
+                compare(attribute, value),
+                Some(Ordering::Greater) | Some(Ordering::Equal)
+            ),
+            "pfx" => match (attribute, value) {
+                (Some(Value::String(x)), Value::String(y)) => x.starts_with(y.as_str()),
+                _ => false,
+            },
+            "r" => match value.as_array().map(Vec::as_slice) {
+                Some([start, end]) => {
+                    matches!(
+                        compare(attribute, start),
+                        Some(Ordering::Greater) | Some(Ordering::Equal)
+                    ) && matches!(
+                        compare(attribute, end),
+                        Some(Ordering::Less) | Some(Ordering::Equal)
+                    )
+                }
+                _ => false,
+            },
+            "contains" => contains(attribute, value),
+            "not_contains" => !contains(attribute, value),
+            _ => false,
+        }
+    })
+}
+
+fn equal(x: &Value, y: &Value) -> bool {
+    match (x.as_f64(), y.as_f64()) {
+        (Some(x), Some(y)) => x == y,
+        _ => x == y,
+    }
+}
+
+fn compare(x: Option<&Value>, y: &Value) -> Option<Ordering> {
+    match (x?, y) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64()?.partial_cmp(&y.as_f64()?),
+        (Value::String(x), Value::String(y)) => Some(x.cmp(y)),
+        _ => None,
+    }
+}
+
+fn contains(x: Option<&Value>, y: &Value) -> bool {
+    match (x, y) {
+        (Some(Value::String(x)), Value::String(y)) => x.contains(y.as_str()),
+        (Some(Value::Array(x)), y) => x.iter().any(|x| equal(x, y)),
+        _ => false,
+    }
+}
+
+fn add(x: &Value, y: &Value) -> Option<Value> {
+    match (x.as_i64(), y.as_i64()) {
+        (Some(x), Some(y)) => x.checked_add(y).map(Value::from),
+        _ => Some(Value::from(x.as_f64()? + y.as_f64()?)),
+    }
+}
+
+/// The current list at a path, for appending or prepending.
+fn list(item: &Value, path: &str) -> Result<Vec<Value>, (StatusCode, Value)> {
+    match get_path(item, path) {
+        None => Ok(Vec::new()),
+        Some(Value::Array(x)) => Ok(x.clone()),
+        Some(_) => Err(error(StatusCode::BAD_REQUEST, "Append on a non-list")),
+    }
+}
+
+/// The values to append or prepend, which may be a single value or a list.
+fn values(value: &Value) -> Vec<Value> {
+    match value {
+        Value::Array(x) => x.clone(),
+        x => vec![x.clone()],
+    }
+}
+
+fn object<'a>(body: &'a Value, name: &str) -> impl Iterator<Item = (&'a String, &'a Value)> {
+    body.get(name)
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+}
+
+fn get_path<'a>(item: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(item, |x, name| x.get(name))
+}
+
+fn set_path(item: &mut Value, path: &str, value: Value) {
+    let mut names = path.split('.').peekable();
+    let mut current = item;
+
+    while let Some(name) = names.next() {
+        if !current.is_object() {
+            *current = Value::Object(Map::new());
+        }
+
+        let object = match current.as_object_mut() {
+            Some(x) => x,
+            None => return,
+        };
+
+        if names.peek().is_none() {
+            object.insert(name.to_string(), value);
+            return;
+        }
+
+        current = object
+            .entry(name)
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+}
+
+fn remove_path(item: &mut Value, path: &str) {
+    let (parent, name) = match path.rfind('.') {
+        Some(i) => (&path[..i], &path[i + 1..]),
+        None => ("", path),
+    };
+
+    let parent = if parent.is_empty() {
+        Some(item)
+    } else {
+        parent.split('.').try_fold(item, |x, name| x.get_mut(name))
+    };
+
+    if let Some(Value::Object(x)) = parent {
+        x.remove(name);
+    }
+}
+
+fn decode(x: &str) -> String {
+    percent_decode_str(x).decode_utf8_lossy().into_owned()
+}
+
+fn error(status: StatusCode, message: &str) -> (StatusCode, Value) {
+    (status, json!({ "errors": [message] }))
+}
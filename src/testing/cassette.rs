@@ -0,0 +1,373 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use std::{error, fmt, fs, io};
+
+use bytes::Bytes;
+use futures::future::{self, BoxFuture, FutureExt};
+use http::{header, HeaderMap, Request, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::transport::{Body, Transport, TransportError};
+
+/// The value which replaces the `x-api-key` header in a cassette.
+const REDACTED: &str = "[redacted]";
+
+/// A [`Transport`](crate::Transport) which records requests and their responses to a JSON file,
+/// and replays them later without a Deta project.
+///
+/// While recording, every request is sent with another transport,
+/// and the file is rewritten after each response, with the `x-api-key` header redacted.
+/// While replaying, each request gets the response of an unused recorded request
+/// with the same method, URL and body, in the order they were recorded.
+/// A request without one fails right away, without being retried,
+/// with [`Error::RequestSendError`](crate::Error::RequestSendError), whose source names the request.
+/// Retries of recorded failures aren't delayed.
+///
+/// The URLs include the project ID, so replay with a client of the same project,
+/// e.g. using [`DetaBuilder::project_id`](crate::DetaBuilder::project_id) with any key.
+/// Requests whose bodies change between runs, like items expiring relative to the current time,
+/// can't be replayed.
+///
+/// # Examples
+///
+/// ```
+/// use deta::testing::MemoryBackend;
+/// use deta::{Deta, Item};
+/// # #[tokio::main]
+/// # async fn main() -> anyhow::Result<()> {
+/// let name = format!("deta_{}_cassette.json", std::process::id());
+/// let path = std::env::temp_dir().join(name);
+///
+/// let deta = Deta::in_memory(MemoryBackend::new()).recording(&path)?;
+/// deta.base("main").put(Item::new_with_key("id", 5)).await?;
+///
+/// let deta = Deta::in_memory(MemoryBackend::new()).replaying(&path)?;
+/// deta.base("main").put(Item::new_with_key("id", 5)).await?;
+///
+/// assert!(deta.base("main").get::<usize>("id").await.is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Cassette {
+    mode: Mode,
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Clone)]
+enum Mode {
+    Record {
+        transport: Arc<dyn Transport>,
+        path: Arc<PathBuf>,
+    },
+    Replay,
+}
+
+#[derive(Default)]
+struct State {
+    interactions: Vec<Interaction>,
+    used: Vec<bool>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Tape {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Interaction {
+    request: Recorded,
+    response: Recorded,
+}
+
+/// A recorded request or response.
+///
+/// A request has a `method` and a `url`, a response has a `status`.
+#[derive(Serialize, Deserialize, Clone)]
+struct Recorded {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    method: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    body: RecordedBody,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum RecordedBody {
+    Empty,
+    Json(Value),
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+impl Cassette {
+    /// Creates a cassette which sends the requests with `transport`, and records them to `path`.
+    ///
+    /// The file is created right away, along with its parent directories, and overwritten if it exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: The JSON file of the cassette.
+    /// * `transport`: The transport which sends the requests.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file can't be written.
+    pub fn record(path: impl AsRef<Path>, transport: impl Transport) -> io::Result<Self> {
+        Self::record_with(path.as_ref(), Arc::new(transport))
+    }
+
+    pub(crate) fn record_with(path: &Path, transport: Arc<dyn Transport>) -> io::Result<Self> {
+        let cassette = Self {
+            mode: Mode::Record {
+                transport,
+                path: Arc::new(path.to_path_buf()),
+            },
+            state: Arc::default(),
+        };
+
+        if let Some(x) = path.parent() {
+            fs::create_dir_all(x)?;
+        }
+        cassette.save()?;
+
+        Ok(cassette)
+    }
+
+    /// Creates a cassette which replays the requests recorded to `path`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: The JSON file of the cassette.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file can't be read, or isn't a cassette.
+    pub fn replay(path: impl AsRef<Path>) -> io::Result<Self> {
+        let json = fs::read(path)?;
+        let Tape { interactions } = serde_json::from_slice(&json)?;
+        let state = State {
+            used: vec![false; interactions.len()],
+            interactions,
+        };
+
+        Ok(Self {
+            mode: Mode::Replay,
+            state: Arc::new(Mutex::new(state)),
+        })
+    }
+
+    /// The number of recorded requests which weren't replayed yet.
+    ///
+    /// Check that it's zero at the end of a test, to make sure every recorded request was sent again.
+    /// While recording, it's always zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::http::Request;
+    /// use deta::testing::{Cassette, MemoryBackend};
+    /// use deta::Transport;
+    /// # #[tokio::main]
+    /// # async fn main() -> anyhow::Result<()> {
+    /// let name = format!("deta_{}_cassette_remaining.json", std::process::id());
+    /// let path = std::env::temp_dir().join(name);
+    /// let get = || Request::get("memory://base/main/items/id").body(bytes::Bytes::new());
+    ///
+    /// let cassette = Cassette::record(&path, MemoryBackend::new())?;
+    /// cassette.send(get()?).await.unwrap();
+    ///
+    /// let cassette = Cassette::replay(&path)?;
+    /// assert_eq!(cassette.remaining(), 1);
+    /// cassette.send(get()?).await.unwrap();
+    /// assert_eq!(cassette.remaining(), 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn remaining(&self) -> usize {
+        self.lock().used.iter().filter(|x| !**x).count()
+    }
+
+    fn save(&self) -> io::Result<()> {
+        if let Mode::Record { path, .. } = &self.mode {
+            let tape = Tape {
+                interactions: self.lock().interactions.clone(),
+            };
+            fs::write(path.as_ref(), serde_json::to_vec_pretty(&tape)?)?;
+        }
+
+        Ok(())
+    }
+
+    fn replayed(&self, request: &Recorded) -> Result<Response<Body>, TransportError> {
+        let mut state = self.lock();
+        let state = &mut *state;
+
+        let found = state
+            .interactions
+            .iter()
+            .zip(&mut state.used)
+            .find(|(x, used)| !**used && x.request.matches(request));
+
+        match found {
+            Some((x, used)) => {
+                *used = true;
+                x.response.to_response()
+            }
+            None => Err(Box::new(Unrecorded(format!(
+                "no recorded request matches {} {}",
+                request.method.as_deref().unwrap_or_default(),
+                request.url.as_deref().unwrap_or_default()
+            )))),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Transport for Cassette {
+    fn send(
+        &self,
+        request: Request<Bytes>,
+    ) -> BoxFuture<'static, Result<Response<Body>, TransportError>> {
+        let recorded = Recorded::request(&request);
+
+        let transport = match &self.mode {
+            Mode::Record { transport, .. } => transport.clone(),
+            Mode::Replay => return future::ready(self.replayed(&recorded)).boxed(),
+        };
+
+        let cassette = self.clone();
+        async move {
+            let (parts, body) = transport.send(request).await?.into_parts();
+            let body = body.bytes().await?;
+
+            let response = Recorded {
+                method: None,
+                url: None,
+                status: Some(parts.status.as_u16()),
+                headers: headers(&parts.headers),
+                body: RecordedBody::new(&body),
+            };
+
+            cassette.lock().interactions.push(Interaction {
+                request: recorded,
+                response,
+            });
+            cassette.save()?;
+
+            Ok(Response::from_parts(parts, Body::from(body)))
+        }
+        .boxed()
+    }
+
+    fn delay(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        match &self.mode {
+            Mode::Record { transport, .. } => transport.delay(duration),
+            Mode::Replay => future::ready(()).boxed(),
+        }
+    }
+}
+
+impl fmt::Debug for Cassette {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = match &self.mode {
+            Mode::Record { path, .. } => format!("Record({})", path.display()),
+            Mode::Replay => "Replay".to_string(),
+        };
+
+        f.debug_struct("Cassette")
+            .field("mode", &mode)
+            .field("interactions", &self.lock().interactions.len())
+            .finish()
+    }
+}
+
+/// The error of a request which wasn't recorded, which isn't retried.
+#[derive(Debug)]
+pub(crate) struct Unrecorded(String);
+
+impl fmt::Display for Unrecorded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl error::Error for Unrecorded {}
+
+impl Recorded {
+    fn request(request: &Request<Bytes>) -> Self {
+        let mut headers = headers(request.headers());
+        if let Some(x) = headers.get_mut("x-api-key") {
+            *x = REDACTED.to_string();
+        }
+
+        Self {
+            method: Some(request.method().to_string()),
+            url: Some(request.uri().to_string()),
+            status: None,
+            headers,
+            body: RecordedBody::new(request.body()),
+        }
+    }
+
+    fn matches(&self, other: &Self) -> bool {
+        self.method == other.method && self.url == other.url && self.body == other.body
+    }
+
+    fn to_response(&self) -> Result<Response<Body>, TransportError> {
+        let status = StatusCode::from_u16(self.status.unwrap_or_default())?;
+        let mut builder = Response::builder().status(status);
+
+        for (name, value) in &self.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+
+        Ok(builder.body(Body::from(self.body.to_bytes()))?)
+    }
+}
+
+impl RecordedBody {
+    fn new(body: &[u8]) -> Self {
+        if body.is_empty() {
+            return Self::Empty;
+        }
+
+        if let Ok(x) = serde_json::from_slice(body) {
+            return Self::Json(x);
+        }
+
+        match std::str::from_utf8(body) {
+            Ok(x) => Self::Text(x.to_string()),
+            Err(_) => Self::Bytes(body.to_vec()),
+        }
+    }
+
+    fn to_bytes(&self) -> Bytes {
+        match self {
+            Self::Empty => Bytes::new(),
+            Self::Json(x) => x.to_string().into(),
+            Self::Text(x) => x.clone().into(),
+            Self::Bytes(x) => x.clone().into(),
+        }
+    }
+}
+
+/// The headers which can be recorded as text.
+fn headers(headers: &HeaderMap) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .filter(|(name, _)| *name != header::CONTENT_LENGTH)
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect()
+}
@@ -0,0 +1,163 @@
+//! Sending the requests of a client with a pluggable HTTP client.
+
+use std::time::Duration;
+use std::{error, fmt, thread};
+
+use bytes::Bytes;
+use futures::channel::oneshot;
+use futures::future::{self, BoxFuture, FutureExt};
+use futures::stream::{self, BoxStream, Stream, StreamExt, TryStreamExt};
+use http::{Request, Response};
+
+/// An error which occurred while sending a request, or receiving its response.
+pub type TransportError = Box<dyn error::Error + Send + Sync>;
+
+/// Sends the requests of a [`Deta`](crate::Deta) client.
+///
+/// With the default `reqwest` feature, it's implemented for `reqwest::Client`, which is used by default.
+/// Implement it to send the requests with another HTTP client, or to record them in tests,
+/// and set it with [`DetaBuilder::transport`](crate::DetaBuilder::transport).
+///
+/// The requests have their method, their full URL, the `x-api-key` header and their body set.
+/// Only failing to get a response is an error, responses with any status code are returned.
+///
+/// Request bodies are always sent whole, so they're held in memory.
+/// Streams are only sent by [`Drive::put_stream`](crate::Drive::put_stream)
+/// and [`Drive::upload_stream`](crate::Drive::upload_stream), split in parts of 10 MB.
+///
+/// # Examples
+///
+/// ```
+/// use deta::http::{Request, Response, StatusCode};
+/// use deta::transport::{Body, Transport, TransportError};
+/// use deta::DetaBuilder;
+/// use futures::future::{self, BoxFuture};
+///
+/// /// Responds to every request with `503 Service Unavailable`.
+/// struct Unavailable;
+///
+/// impl Transport for Unavailable {
+///     fn send(
+///         &self,
+///         _: Request<bytes::Bytes>,
+///     ) -> BoxFuture<'static, Result<Response<Body>, TransportError>> {
+///         let mut response = Response::new(Body::empty());
+///         *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+///         Box::pin(future::ready(Ok(response)))
+///     }
+/// }
+///
+/// # fn main() -> deta::Result<()> {
+/// let deta = DetaBuilder::new("a0abcdef_thisisaverysecretkey")
+///     .transport(Unavailable)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait Transport: Send + Sync + 'static {
+    /// Sends a request, and returns its response.
+    fn send(
+        &self,
+        request: Request<Bytes>,
+    ) -> BoxFuture<'static, Result<Response<Body>, TransportError>>;
+
+    /// Waits for `duration`, before a failed request is sent again.
+    ///
+    /// The default implementation waits on a separate thread, so it works with any runtime.
+    fn delay(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        let (sender, receiver) = oneshot::channel();
+
+        thread::spawn(move || {
+            thread::sleep(duration);
+            let _ = sender.send(());
+        });
+
+        receiver.map(|_| ()).boxed()
+    }
+}
+
+/// The body of a response, received in chunks.
+pub struct Body {
+    chunks: BoxStream<'static, Result<Bytes, TransportError>>,
+}
+
+impl Body {
+    /// Creates a body out of a stream of chunks.
+    pub fn wrap_stream<S>(chunks: S) -> Self
+    where
+        S: Stream<Item = Result<Bytes, TransportError>> + Send + 'static,
+    {
+        Self {
+            chunks: chunks.boxed(),
+        }
+    }
+
+    /// Creates an empty body.
+    pub fn empty() -> Self {
+        Self::wrap_stream(stream::empty())
+    }
+
+    /// Receives the whole body.
+    pub async fn bytes(self) -> Result<Bytes, TransportError> {
+        let chunks = self.chunks.try_collect::<Vec<_>>().await?;
+
+        match chunks.len() {
+            1 => Ok(chunks.into_iter().next().unwrap_or_default()),
+            _ => Ok(chunks.concat().into()),
+        }
+    }
+
+    /// The chunks of the body, as they're received.
+    pub fn into_stream(self) -> impl Stream<Item = Result<Bytes, TransportError>> {
+        self.chunks
+    }
+}
+
+impl From<Bytes> for Body {
+    fn from(bytes: Bytes) -> Self {
+        Self::wrap_stream(stream::once(future::ready(Ok(bytes))))
+    }
+}
+
+impl From<Vec<u8>> for Body {
+    fn from(bytes: Vec<u8>) -> Self {
+        Bytes::from(bytes).into()
+    }
+}
+
+impl fmt::Debug for Body {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Body").finish()
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl Transport for reqwest::Client {
+    fn send(
+        &self,
+        request: Request<Bytes>,
+    ) -> BoxFuture<'static, Result<Response<Body>, TransportError>> {
+        let (parts, body) = request.into_parts();
+        let request = self
+            .request(parts.method, &parts.uri.to_string())
+            .headers(parts.headers)
+            .body(body);
+
+        async move {
+            let response = request.send().await?;
+
+            let mut builder = Response::builder().status(response.status());
+            if let Some(x) = builder.headers_mut() {
+                x.extend(response.headers().clone());
+            }
+
+            let body = response.bytes_stream().map_err(|e| Box::new(e) as _);
+            Ok(builder.body(Body::wrap_stream(body))?)
+        }
+        .boxed()
+    }
+
+    fn delay(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        tokio::time::delay_for(duration).boxed()
+    }
+}
@@ -0,0 +1,201 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use futures::stream::Stream;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{Base, DetaItem, IntoItem, Item, PutManyReport, Query, Result, Update};
+
+/// A handle to a Deta Base, bound to a single type of items.
+///
+/// Get one with [`Deta::typed_base`](crate::Deta::typed_base) or [`Base::typed`](crate::Base::typed).
+/// Every item written to it or read from it is a `T`,
+/// so the methods don't need type annotations, and items of other types can't be mixed in.
+/// The methods behave like the ones of [`Base`](crate::Base), and fail with the same errors.
+///
+/// # Examples
+///
+/// ```
+/// use deta::{Deta, Item, Query};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize, Debug, PartialEq)]
+/// struct User {
+///     name: String,
+///     age: u32,
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> deta::Result<()> {
+/// let deta = Deta::new()?;
+///
+/// let users = deta.typed_base::<User>("users");
+/// let user = User {
+///     name: "Jimmy".to_string(),
+///     age: 32,
+/// };
+/// users.put(Item::new_with_key("jimmy", user)).await?;
+///
+/// let user = users.get("jimmy").await?;
+/// assert_eq!(user.age, 32);
+///
+/// let adults = users.query(Query::new().greater_than_or_equal("age", 18)).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct TypedBase<T> {
+    base: Base,
+    item: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for TypedBase<T> {
+    fn clone(&self) -> Self {
+        Self {
+            base: self.base.clone(),
+            item: PhantomData,
+        }
+    }
+}
+
+impl<T> From<TypedBase<T>> for Base {
+    fn from(base: TypedBase<T>) -> Self {
+        base.base
+    }
+}
+
+impl<T> TypedBase<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    pub(crate) fn new(base: Base) -> Self {
+        Self {
+            base,
+            item: PhantomData,
+        }
+    }
+
+    /// The name of the base.
+    pub fn name(&self) -> &str {
+        self.base.name()
+    }
+
+    /// The untyped handle to the same base.
+    pub fn as_base(&self) -> &Base {
+        &self.base
+    }
+
+    /// Converts back to the untyped handle to the same base.
+    pub fn into_base(self) -> Base {
+        self.base
+    }
+
+    /// Get a stored item.
+    ///
+    /// See [`Base::get`](crate::Base::get).
+    pub async fn get(&self, key: impl fmt::Display) -> Result<T> {
+        self.base.get(key).await
+    }
+
+    /// Get a stored item, or `None` if no item with `key` exists.
+    ///
+    /// See [`Base::get_opt`](crate::Base::get_opt).
+    pub async fn get_opt(&self, key: impl fmt::Display) -> Result<Option<T>> {
+        self.base.get_opt(key).await
+    }
+
+    /// Get a stored item, along with its key and expiry.
+    ///
+    /// See [`Base::get_item`](crate::Base::get_item).
+    pub async fn get_item(&self, key: impl fmt::Display) -> Result<Item<T>> {
+        self.base.get_item(key).await
+    }
+
+    /// Fetch all the stored items matching a query.
+    ///
+    /// See [`Base::query`](crate::Base::query).
+    pub async fn query(&self, query: Query) -> Result<Vec<Item<T>>> {
+        self.base.query(query).await
+    }
+
+    /// Stream all the stored items matching a query.
+    ///
+    /// See [`Base::query_stream`](crate::Base::query_stream).
+    pub fn query_stream(&self, query: Query) -> impl Stream<Item = Result<Item<T>>> {
+        self.base.query_stream(query)
+    }
+
+    /// Delete a stored item.
+    ///
+    /// See [`Base::delete`](crate::Base::delete).
+    pub async fn delete(&self, key: impl fmt::Display) -> Result<()> {
+        self.base.delete(key).await
+    }
+
+    /// Delete a stored item, if it exists.
+    ///
+    /// See [`Base::delete_existing`](crate::Base::delete_existing).
+    pub async fn delete_existing(&self, key: impl fmt::Display) -> Result<bool> {
+        self.base.delete_existing(key).await
+    }
+
+    /// Stores an item, overwriting an item with the same key.
+    ///
+    /// See [`Base::put`](crate::Base::put).
+    pub async fn put(&self, item: impl IntoItem<Value = T>) -> Result<String> {
+        self.base.put(item).await
+    }
+
+    /// Stores multiple items in a single request.
+    ///
+    /// See [`Base::put_many`](crate::Base::put_many).
+    pub async fn put_many<I>(&self, items: Vec<I>) -> Result<PutManyReport<I>>
+    where
+        I: IntoItem<Value = T>,
+    {
+        self.base.put_many(items).await
+    }
+
+    /// Stores any number of items, in batches.
+    ///
+    /// See [`Base::put_all`](crate::Base::put_all).
+    pub async fn put_all<I>(&self, items: Vec<I>, concurrency: usize) -> Result<PutManyReport<I>>
+    where
+        I: IntoItem<Value = T>,
+    {
+        self.base.put_all(items, concurrency).await
+    }
+
+    /// Creates a new item only if no item with the same `key` exists.
+    ///
+    /// See [`Base::insert`](crate::Base::insert).
+    pub async fn insert(&self, item: impl IntoItem<Value = T>) -> Result<String> {
+        self.base.insert(item).await
+    }
+
+    /// Updates an item only if an item with `key` exists.
+    ///
+    /// See [`Base::update`](crate::Base::update).
+    pub async fn update(&self, key: impl fmt::Display, update: Update) -> Result<()> {
+        self.base.update(key, update).await
+    }
+}
+
+impl<T> TypedBase<T>
+where
+    T: DetaItem,
+{
+    /// Get a stored struct, with its key field set.
+    ///
+    /// See [`Base::get_keyed`](crate::Base::get_keyed).
+    pub async fn get_keyed(&self, key: impl fmt::Display) -> Result<T> {
+        self.base.get_keyed(key).await
+    }
+
+    /// Fetch all the stored structs matching a query, with their key field set.
+    ///
+    /// See [`Base::query_keyed`](crate::Base::query_keyed).
+    pub async fn query_keyed(&self, query: Query) -> Result<Vec<T>> {
+        self.base.query_keyed(query).await
+    }
+}
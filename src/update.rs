@@ -1,10 +1,19 @@
 use std::collections::HashMap;
 use std::string;
+use std::time::{Duration, SystemTime};
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{item, Error, Result};
 
 /// Only used for update requests.
 ///
+/// The values keep their JSON types, so numbers stay numbers
+/// and structs are stored as nested objects.
+/// If a value can't be serialized to JSON, sending the update fails with
+/// [`Error::JSONSerializingFailed`](crate::Error::JSONSerializingFailed).
+///
 /// # Examples
 ///
 /// ```
@@ -19,14 +28,20 @@ use serde::{Deserialize, Serialize};
 ///     .prepend("likes", "noodles")
 ///     .delete("profile.hometown")
 ///     .delete("on_mobile");
+///
+/// let json = serde_json::to_value(&update).unwrap();
+/// assert_eq!(json["set"]["profile.age"], 33);
+/// assert_eq!(json["increment"]["purchases"], 2);
 /// ```
 #[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
 pub struct Update {
-    set: HashMap<String, String>,
-    increment: HashMap<String, String>,
-    append: HashMap<String, Vec<String>>,
-    prepend: HashMap<String, Vec<String>>,
+    set: HashMap<String, Value>,
+    increment: HashMap<String, Value>,
+    append: HashMap<String, Vec<Value>>,
+    prepend: HashMap<String, Vec<Value>>,
     delete: Vec<String>,
+    #[serde(skip)]
+    invalid: Vec<String>,
 }
 
 impl Update {
@@ -50,8 +65,11 @@ impl Update {
     /// use deta::Update;
     /// let update = Update::new().set("name", "Jimmy");
     /// ```
-    pub fn set(mut self, key: impl string::ToString, value: impl string::ToString) -> Self {
-        self.set.insert(key.to_string(), value.to_string());
+    pub fn set(mut self, key: impl string::ToString, value: impl Serialize) -> Self {
+        let key = key.to_string();
+        if let Some(x) = self.value(&key, value) {
+            self.set.insert(key, x);
+        }
         self
     }
 
@@ -63,8 +81,11 @@ impl Update {
     /// use deta::Update;
     /// let update = Update::new().increment("age", 1);
     /// ```
-    pub fn increment(mut self, key: impl string::ToString, value: impl string::ToString) -> Self {
-        self.increment.insert(key.to_string(), value.to_string());
+    pub fn increment(mut self, key: impl string::ToString, value: impl Serialize) -> Self {
+        let key = key.to_string();
+        if let Some(x) = self.value(&key, value) {
+            self.increment.insert(key, x);
+        }
         self
     }
 
@@ -76,11 +97,11 @@ impl Update {
     /// use deta::Update;
     /// let update = Update::new().append("likes", "ramen");
     /// ```
-    pub fn append(mut self, key: impl string::ToString, value: impl string::ToString) -> Self {
-        self.append
-            .entry(key.to_string())
-            .or_insert_with(Vec::new)
-            .push(value.to_string());
+    pub fn append(mut self, key: impl string::ToString, value: impl Serialize) -> Self {
+        let key = key.to_string();
+        if let Some(x) = self.value(&key, value) {
+            self.append.entry(key).or_default().push(x);
+        }
         self
     }
 
@@ -92,11 +113,11 @@ impl Update {
     /// use deta::Update;
     /// let update = Update::new().append("likes", "noodles");
     /// ```
-    pub fn prepend(mut self, key: impl string::ToString, value: impl string::ToString) -> Self {
-        self.prepend
-            .entry(key.to_string())
-            .or_insert_with(Vec::new)
-            .push(value.to_string());
+    pub fn prepend(mut self, key: impl string::ToString, value: impl Serialize) -> Self {
+        let key = key.to_string();
+        if let Some(x) = self.value(&key, value) {
+            self.prepend.entry(key).or_default().push(x);
+        }
         self
     }
 
@@ -112,4 +133,48 @@ impl Update {
         self.delete.push(key.to_string());
         self
     }
+
+    /// To make the item expire after a duration from now.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::Update;
+    /// use std::time::Duration;
+    /// let update = Update::new().expire_in(Duration::from_secs(300));
+    /// ```
+    pub fn expire_in(self, duration: Duration) -> Self {
+        self.expire_at(SystemTime::now() + duration)
+    }
+
+    /// To make the item expire at a point in time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deta::Update;
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// let update = Update::new().expire_at(UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+    /// ```
+    pub fn expire_at(self, time: SystemTime) -> Self {
+        self.set("__expires", item::timestamp(time))
+    }
+
+    /// Fails if a value wasn't serialized successfully, naming its attribute.
+    pub(crate) fn check(&self) -> Result<()> {
+        match self.invalid.first() {
+            Some(x) => Err(Error::serializing(x)),
+            None => Ok(()),
+        }
+    }
+
+    fn value(&mut self, key: &str, value: impl Serialize) -> Option<Value> {
+        match serde_json::to_value(value) {
+            Ok(x) => Some(x),
+            Err(e) => {
+                self.invalid.push(format!("{}: {}", key, e));
+                None
+            }
+        }
+    }
 }
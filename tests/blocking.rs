@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use deta::blocking::Deta;
+    use deta::testing::MemoryBackend;
+    use deta::{Error, Item, Update};
+
+    #[test]
+    fn operations() -> anyhow::Result<()> {
+        let deta = Deta::in_memory(MemoryBackend::new())?;
+        let base = deta.base("test");
+
+        assert_eq!(base.put(Item::new_with_key("a", 1usize))?, "a");
+        let result = base.insert(Item::new_with_key("a", 2usize));
+        assert!(matches!(result, Err(Error::KeyConflict(_))));
+        assert_eq!(base.get::<usize>("a")?, 1);
+
+        base.update("a", Update::new().increment("value", 2))?;
+        assert_eq!(base.get::<usize>("a")?, 3);
+
+        let items = vec![Item::new_with_key("b", 4usize), Item::new_with_key("c", 5)];
+        let report = base.put_many(items)?;
+        assert!(report.is_complete());
+        assert_eq!(report.processed(), ["b", "c"]);
+
+        base.delete("a")?;
+        assert_eq!(base.get_opt::<usize>("a")?, None);
+        assert!(!base.delete_existing("a")?);
+        assert!(base.delete_existing("b")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn shared_between_threads() -> anyhow::Result<()> {
+        let backend = MemoryBackend::new();
+        let base = Deta::in_memory(backend.clone())?.base("test");
+
+        let threads = (0..4usize)
+            .map(|x| {
+                let base = base.clone();
+                thread::spawn(move || base.put(Item::new_with_key(x, x)))
+            })
+            .collect::<Vec<_>>();
+
+        for x in threads {
+            x.join().expect("thread panicked")?;
+        }
+
+        let other = Deta::in_memory(backend)?.base("test");
+        for x in 0..4usize {
+            assert_eq!(other.get::<usize>(x)?, x);
+        }
+
+        Ok(())
+    }
+}
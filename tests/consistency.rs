@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod tests {
-    use deta::{Deta, Item};
+    use deta::{Deta, Item, Query};
 
     #[tokio::test]
     async fn put_get() -> anyhow::Result<()> {
@@ -12,7 +12,7 @@ mod tests {
         let item = Item::new_with_key("Hello", 5usize);
 
         deta.insert(item).await?;
-        let Item { value, .. }: Item<usize> = deta.get("Hello").await?;
+        let value: usize = deta.get("Hello").await?;
 
         assert_eq!(value, 5usize);
 
@@ -34,7 +34,7 @@ mod tests {
                 let item = Item::new_with_key(x, y);
                 deta.delete(x).await?;
                 deta.insert(item).await?;
-                let Item { value, .. }: Item<usize> = deta.get(x).await?;
+                let value: usize = deta.get(x).await?;
                 assert_eq!(value, y);
 
                 deta::Result::Ok(())
@@ -43,4 +43,49 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn put_query() -> anyhow::Result<()> {
+        let deta = Deta::new()?;
+        let deta = deta.base("test_query");
+
+        for x in 0..5usize {
+            deta.put(Item::new_with_key(format!("query_{}", x), x))
+                .await?;
+        }
+
+        let mut items: Vec<Item<usize>> = deta
+            .query(
+                Query::new()
+                    .prefix("key", "query_")
+                    .less_than("value", 2)
+                    .or()
+                    .equal("value", 4),
+            )
+            .await?;
+        items.sort_by_key(|x| x.value);
+
+        let values = items.into_iter().map(|x| x.value).collect::<Vec<_>>();
+        assert_eq!(values, vec![0, 1, 4]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn put_expires() -> anyhow::Result<()> {
+        use std::time::Duration;
+
+        let deta = Deta::new()?;
+        let deta = deta.base("test_expires");
+
+        let item = Item::new_with_key("expires", 5usize).expire_in(Duration::from_secs(300));
+        let expires = item.expires;
+        deta.put(item).await?;
+
+        let items: Vec<Item<usize>> = deta.query(Query::new().equal("key", "expires")).await?;
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].expires, expires);
+
+        Ok(())
+    }
 }
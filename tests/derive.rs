@@ -0,0 +1,118 @@
+#[cfg(test)]
+mod tests {
+    use deta::testing::MemoryBackend;
+    use deta::{Deta, DetaItem, Error, Item, Query};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, DetaItem, Debug, Clone, PartialEq)]
+    struct User {
+        #[deta(key)]
+        id: String,
+        name: String,
+        age: u32,
+    }
+
+    #[derive(Serialize, Deserialize, DetaItem, Debug, PartialEq)]
+    #[serde(rename_all = "camelCase")]
+    struct Order {
+        #[deta(key)]
+        #[serde(rename = "orderId")]
+        order_id: Option<String>,
+        total_price: u32,
+    }
+
+    fn user(id: &str, age: u32) -> User {
+        User {
+            id: id.to_string(),
+            name: id.to_uppercase(),
+            age,
+        }
+    }
+
+    #[tokio::test]
+    async fn put_get_keyed() -> anyhow::Result<()> {
+        let deta = Deta::in_memory(MemoryBackend::new());
+        let base = deta.base("users");
+
+        assert_eq!(base.put(user("a", 20)).await?, "a");
+        let result = base.insert(user("a", 21)).await;
+        assert!(matches!(result, Err(Error::KeyConflict(_))));
+
+        assert_eq!(base.get_keyed::<User>("a").await?, user("a", 20));
+
+        let item: Item<serde_json::Value> = base.query(Query::new()).await?.remove(0);
+        assert_eq!(item.key.as_deref(), Some("a"));
+        assert!(item.value.get("id").is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn put_many_query_keyed() -> anyhow::Result<()> {
+        let deta = Deta::in_memory(MemoryBackend::new());
+        let users = deta.typed_base::<User>("users");
+
+        let report = users
+            .put_many(vec![user("a", 20), user("b", 30), user("c", 40)])
+            .await?;
+        assert_eq!(report.processed(), ["a", "b", "c"]);
+        assert!(report.is_complete());
+
+        let users = users
+            .query_keyed(Query::new().greater_than("age", 25))
+            .await?;
+        assert_eq!(users, vec![user("b", 30), user("c", 40)]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn generated_keys() -> anyhow::Result<()> {
+        let deta = Deta::in_memory(MemoryBackend::new());
+        let orders = deta.typed_base::<Order>("orders");
+
+        let order = Order {
+            order_id: None,
+            total_price: 5,
+        };
+        let key = orders.put(order).await?;
+
+        let order = orders.get_keyed(&key).await?;
+        assert_eq!(order.order_id, Some(key));
+        assert_eq!(order.total_price, 5);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn typed_reads() -> anyhow::Result<()> {
+        let deta = Deta::in_memory(MemoryBackend::new());
+        let users = deta.typed_base::<User>("users");
+
+        users.put_many(vec![user("a", 20), user("b", 30)]).await?;
+
+        assert_eq!(users.get_keyed("a").await?, user("a", 20));
+        let values = users
+            .query_keyed(Query::new().greater_than("age", 25))
+            .await?;
+        assert_eq!(values, vec![user("b", 30)]);
+
+        // The other reads only read the attributes, which don't include the key field.
+        let result = users.get("a").await;
+        assert!(matches!(result, Err(Error::JSONDeserializingFailed(_))));
+        let source = std::error::Error::source(result.as_ref().unwrap_err()).unwrap();
+        assert_eq!(source.to_string(), "missing field `id`");
+
+        let orders = deta.typed_base::<Order>("orders");
+        let order = Order {
+            order_id: None,
+            total_price: 5,
+        };
+        let key = orders.put(order).await?;
+        let item = orders.get_item(&key).await?;
+        assert_eq!(item.key, Some(key));
+        assert_eq!(item.value.order_id, None);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,310 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use bytes::Bytes;
+    use deta::http::{header, Method, Request, Response, StatusCode};
+    use deta::transport::{Body, Transport, TransportError};
+    use deta::{Deleted, DetaBuilder, Drive, Error, FilePage};
+    use futures::future::{self, BoxFuture};
+    use futures::TryStreamExt;
+    use serde_json::json;
+
+    type Respond = dyn Fn(&Sent) -> (StatusCode, String) + Send + Sync;
+
+    /// A request sent to the fake Drive, with its path relative to the drive.
+    #[derive(Debug, Clone, PartialEq)]
+    struct Sent {
+        method: Method,
+        target: String,
+        content_type: Option<String>,
+        body: Bytes,
+    }
+
+    #[derive(Clone)]
+    struct FakeDrive {
+        sent: Arc<Mutex<Vec<Sent>>>,
+        respond: Arc<Respond>,
+    }
+
+    impl Transport for FakeDrive {
+        fn send(
+            &self,
+            request: Request<Bytes>,
+        ) -> BoxFuture<'static, Result<Response<Body>, TransportError>> {
+            assert_eq!(request.headers()["x-api-key"], "abc_secret");
+
+            let uri = request.uri();
+            let target = uri.path_and_query().map(|x| x.as_str()).unwrap_or_default();
+            let sent = Sent {
+                method: request.method().clone(),
+                target: target.trim_start_matches("/v1/abc/files/").to_string(),
+                content_type: request
+                    .headers()
+                    .get(header::CONTENT_TYPE)
+                    .map(|x| x.to_str().unwrap().to_string()),
+                body: request.into_body(),
+            };
+
+            let (status, body) = (self.respond)(&sent);
+            self.sent.lock().unwrap().push(sent);
+
+            let mut response = Response::new(Body::from(body.into_bytes()));
+            *response.status_mut() = status;
+            Box::pin(future::ready(Ok(response)))
+        }
+    }
+
+    fn drive<F>(respond: F) -> anyhow::Result<(Drive, Arc<Mutex<Vec<Sent>>>)>
+    where
+        F: Fn(&Sent) -> (StatusCode, String) + Send + Sync + 'static,
+    {
+        let fake = FakeDrive {
+            sent: Arc::default(),
+            respond: Arc::new(respond),
+        };
+        let sent = fake.sent.clone();
+        let deta = DetaBuilder::new("abc_secret")
+            .drive_endpoint("http://drive/v1")
+            .transport(fake)
+            .build()?;
+
+        Ok((deta.drive("files"), sent))
+    }
+
+    fn ok(body: serde_json::Value) -> (StatusCode, String) {
+        (StatusCode::OK, body.to_string())
+    }
+
+    #[tokio::test]
+    async fn put_headers() -> anyhow::Result<()> {
+        let (drive, sent) = drive(|_| ok(json!({ "name": "stored" })))?;
+
+        assert_eq!(drive.put("a b.txt", "Hello").await?, "stored");
+        drive
+            .put_with_content_type("index.html", "text/html", vec![1u8, 2])
+            .await?;
+
+        let result = drive.put_with_content_type("x", "text/\n", "").await;
+        assert!(matches!(result, Err(Error::InvalidContentType)));
+
+        let sent = sent.lock().unwrap().clone();
+        assert_eq!(
+            sent,
+            vec![
+                Sent {
+                    method: Method::POST,
+                    target: "files?name=a%20b.txt".to_string(),
+                    content_type: None,
+                    body: Bytes::from("Hello"),
+                },
+                Sent {
+                    method: Method::POST,
+                    target: "files?name=index.html".to_string(),
+                    content_type: Some("text/html".to_string()),
+                    body: Bytes::from(vec![1u8, 2]),
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn get_file() -> anyhow::Result<()> {
+        let (drive, sent) = drive(|x| match x.target.as_str() {
+            "files/download?name=a.txt" => (StatusCode::OK, "Hello".to_string()),
+            _ => (
+                StatusCode::NOT_FOUND,
+                json!({ "errors": ["Not found"] }).to_string(),
+            ),
+        })?;
+
+        let chunks = drive.get("a.txt").await?.try_collect::<Vec<_>>().await?;
+        assert_eq!(chunks.concat(), b"Hello");
+
+        let result = drive.get("b.txt").await;
+        match result {
+            Err(Error::FileNotFound(e)) => {
+                assert_eq!(e.status(), Some(StatusCode::NOT_FOUND));
+                assert_eq!(e.messages(), ["Not found"]);
+            }
+            _ => panic!("expected FileNotFound"),
+        }
+
+        assert_eq!(sent.lock().unwrap().len(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn list_pages() -> anyhow::Result<()> {
+        let (drive, sent) = drive(|x| match x.target.as_str() {
+            "files?prefix=f" | "files?prefix=f&limit=2" => {
+                ok(json!({ "paging": { "size": 2, "last": "f1" }, "names": ["f0", "f1"] }))
+            }
+            "files?prefix=f&last=f1" => {
+                ok(json!({ "paging": { "size": 2, "last": "f3" }, "names": ["f2", "f3"] }))
+            }
+            "files?prefix=f&last=f3" => ok(json!({ "paging": { "size": 1 }, "names": ["f4"] })),
+            _ => (StatusCode::BAD_REQUEST, json!({ "errors": [] }).to_string()),
+        })?;
+
+        let page = drive.list_page("f", Some(2), None).await?;
+        assert_eq!(
+            page,
+            FilePage {
+                names: vec!["f0".to_string(), "f1".to_string()],
+                last: Some("f1".to_string()),
+            }
+        );
+
+        let names = drive.list("f").await?;
+        assert_eq!(names, ["f0", "f1", "f2", "f3", "f4"]);
+        assert_eq!(sent.lock().unwrap().len(), 4);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn delete_many() -> anyhow::Result<()> {
+        let (drive, sent) = drive(|_| {
+            ok(json!({ "deleted": ["a.txt"], "failed": { "b.txt": "permission denied" } }))
+        })?;
+
+        let deleted = drive.delete_many(vec!["a.txt", "b.txt"]).await?;
+        assert_eq!(
+            deleted,
+            Deleted {
+                deleted: vec!["a.txt".to_string()],
+                failed: vec![("b.txt".to_string(), "permission denied".to_string())]
+                    .into_iter()
+                    .collect(),
+            }
+        );
+
+        let names = vec!["x"; 1001];
+        assert!(matches!(
+            drive.delete_many(names).await,
+            Err(Error::VecTooLong)
+        ));
+
+        let sent = sent.lock().unwrap().clone();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].method, Method::DELETE);
+        assert_eq!(sent[0].target, "files");
+        assert_eq!(sent[0].content_type.as_deref(), Some("application/json"));
+        let body: serde_json::Value = serde_json::from_slice(&sent[0].body)?;
+        assert_eq!(body, json!({ "names": ["a.txt", "b.txt"] }));
+
+        Ok(())
+    }
+
+    /// Responds to the requests of a chunked upload, failing the part `fail`.
+    fn upload(x: &Sent, fail: usize) -> (StatusCode, String) {
+        let fail = format!("uploads/u1/parts?name=big&part={}", fail);
+
+        match (&x.method, x.target.as_str()) {
+            (&Method::POST, "uploads?name=big") => ok(json!({ "name": "big", "upload_id": "u1" })),
+            (&Method::POST, target) if target == fail => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                json!({ "errors": ["part failed"] }).to_string(),
+            ),
+            (&Method::POST, target) if target.starts_with("uploads/u1/parts?name=big&part=") => {
+                ok(json!({}))
+            }
+            (&Method::PATCH, "uploads/u1?name=big") | (&Method::DELETE, "uploads/u1?name=big") => {
+                ok(json!({ "name": "big", "upload_id": "u1" }))
+            }
+            _ => (StatusCode::BAD_REQUEST, json!({ "errors": [] }).to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn upload_aborts() -> anyhow::Result<()> {
+        let (drive, sent) = drive(|x| upload(x, 2))?;
+
+        let file = futures::io::Cursor::new(vec![0u8; 25 * 1024 * 1024]);
+        match drive.upload_stream("big", file).await {
+            Err(Error::ServerError(e)) => assert_eq!(e.messages(), ["part failed"]),
+            _ => panic!("expected the error of the part"),
+        }
+
+        let sent = sent.lock().unwrap().clone();
+        let last = sent.last().unwrap();
+        assert_eq!(last.method, Method::DELETE);
+        assert_eq!(last.target, "uploads/u1?name=big");
+        assert!(sent.iter().all(|x| x.method != Method::PATCH));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn upload_parts() -> anyhow::Result<()> {
+        const PART_SIZE: usize = 10 * 1024 * 1024;
+
+        let (drive, sent) = drive(|x| upload(x, 0))?;
+
+        let file = futures::io::Cursor::new(vec![1u8; 2 * PART_SIZE]);
+        assert_eq!(drive.upload_stream("big", file).await?, "big");
+
+        let mut parts = sent
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|x| x.target.contains("/parts?"))
+            .map(|x| (x.target.clone(), x.body.len()))
+            .collect::<Vec<_>>();
+        parts.sort();
+        assert_eq!(
+            parts,
+            vec![
+                ("uploads/u1/parts?name=big&part=1".to_string(), PART_SIZE),
+                ("uploads/u1/parts?name=big&part=2".to_string(), PART_SIZE),
+            ]
+        );
+        assert_eq!(sent.lock().unwrap().last().unwrap().method, Method::PATCH);
+
+        sent.lock().unwrap().clear();
+        let file = futures::io::Cursor::new(Vec::new());
+        drive.upload_stream("big", file).await?;
+
+        let targets = sent
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|x| x.target.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            targets,
+            [
+                "uploads?name=big",
+                "uploads/u1/parts?name=big&part=1",
+                "uploads/u1?name=big",
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn put_stream() -> anyhow::Result<()> {
+        let (drive, sent) = drive(|x| upload(x, 0))?;
+
+        let chunks = futures::stream::iter(vec![Ok("Hello, "), Ok("World!")]);
+        assert_eq!(drive.put_stream("big", chunks).await?, "big");
+
+        let part = sent.lock().unwrap()[1].clone();
+        assert_eq!(part.target, "uploads/u1/parts?name=big&part=1");
+        assert_eq!(part.body, "Hello, World!");
+
+        sent.lock().unwrap().clear();
+        let error = std::io::Error::other("broken");
+        let chunks = futures::stream::iter(vec![Ok("Hello, "), Err(error)]);
+        let result = drive.put_stream("big", chunks).await;
+        assert!(matches!(result, Err(Error::ReadError(_))));
+        assert_eq!(sent.lock().unwrap().last().unwrap().method, Method::DELETE);
+
+        Ok(())
+    }
+}
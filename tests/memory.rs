@@ -0,0 +1,752 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use bytes::Bytes;
+    use deta::http::{Request, Response, StatusCode};
+    use deta::testing::MemoryBackend;
+    use deta::transport::{Body, Transport, TransportError};
+    use deta::{Deta, DetaBuilder, Error, Item, Query, RetryPolicy, Update};
+    use futures::future::BoxFuture;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct User {
+        name: String,
+        age: u32,
+        likes: Vec<String>,
+    }
+
+    /// Sends a request of the project `abc` at `http://localhost/v1` to the memory backend.
+    fn serve(
+        backend: &MemoryBackend,
+        request: Request<Bytes>,
+    ) -> BoxFuture<'static, Result<Response<Body>, TransportError>> {
+        let path = request.uri().path().trim_start_matches("/v1/abc");
+        let uri = format!("memory://base{}", path);
+        let (mut parts, body) = request.into_parts();
+        parts.uri = uri.parse().unwrap();
+        backend.send(Request::from_parts(parts, body))
+    }
+
+    #[tokio::test]
+    async fn insert_conflict() -> anyhow::Result<()> {
+        let deta = Deta::in_memory(MemoryBackend::new());
+        let deta = deta.base("test");
+
+        deta.insert(Item::new_with_key("Hello", 5usize)).await?;
+        let result = deta.insert(Item::new_with_key("Hello", 6usize)).await;
+        assert!(matches!(result, Err(Error::KeyConflict(_))));
+
+        let value: usize = deta.get("Hello").await?;
+        assert_eq!(value, 5);
+
+        deta.delete("Hello").await?;
+        assert!(deta.get::<usize>("Hello").await.unwrap_err().is_not_found());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn put_generates_keys() -> anyhow::Result<()> {
+        let deta = Deta::in_memory(MemoryBackend::new());
+        let deta = deta.base("test");
+
+        let first = deta.put(Item::new(1usize)).await?;
+        let second = deta.put(Item::new(2usize)).await?;
+        assert_ne!(first, second);
+
+        let value: usize = deta.get(&second).await?;
+        assert_eq!(value, 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn update_operations() -> anyhow::Result<()> {
+        let deta = Deta::in_memory(MemoryBackend::new());
+        let deta = deta.base("test");
+
+        let user = User {
+            name: "Jimmy".to_string(),
+            age: 32,
+            likes: vec!["ramen".to_string()],
+        };
+        deta.put(Item::new_with_key("user-a", user)).await?;
+
+        deta.update(
+            "user-a",
+            Update::new()
+                .increment("age", 1)
+                .append("likes", "sushi")
+                .prepend("likes", "noodles")
+                .set("profile.active", true),
+        )
+        .await?;
+
+        let user: User = deta.get("user-a").await?;
+        assert_eq!(user.age, 33);
+        assert_eq!(user.likes, vec!["noodles", "ramen", "sushi"]);
+
+        let result = deta.update("user-b", Update::new().set("age", 1)).await;
+        assert!(matches!(result, Err(Error::KeyNonexistent(_))));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn query_filters() -> anyhow::Result<()> {
+        let deta = Deta::in_memory(MemoryBackend::new());
+        let deta = deta.base("test");
+
+        let items = (0..40usize)
+            .map(|x| Item::new_with_key(format!("{:02}", x), x))
+            .collect::<Vec<_>>();
+        let report = deta.put_all(items, 2).await?;
+        assert_eq!(report.processed().len(), 40);
+        assert!(report.is_complete());
+
+        let items: Vec<Item<usize>> = deta
+            .query(
+                Query::new()
+                    .range("value", 10, 12)
+                    .or()
+                    .prefix("key", "3")
+                    .not_equal("value", 35)
+                    .limit(3),
+            )
+            .await?;
+        let values = items.into_iter().map(|x| x.value).collect::<Vec<_>>();
+        assert_eq!(values, vec![10, 11, 12, 30, 31, 32, 33, 34, 36, 37, 38, 39]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn items_expire() -> anyhow::Result<()> {
+        let backend = MemoryBackend::new();
+        let deta = Deta::in_memory(backend.clone());
+        let deta = deta.base("test");
+
+        let item = Item::new_with_key("session", 5usize).expire_in(Duration::from_secs(60));
+        deta.put(item).await?;
+        deta.put(Item::new_with_key("forever", 6usize)).await?;
+
+        backend.advance(Duration::from_secs(30));
+        assert_eq!(deta.get::<usize>("session").await?, 5);
+
+        backend.advance(Duration::from_secs(60));
+        assert!(deta.get::<usize>("session").await.is_err());
+
+        let items: Vec<Item<usize>> = deta.query(Query::new()).await?;
+        assert_eq!(items, vec![Item::new_with_key("forever", 6)]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn retries_transient_failures() -> anyhow::Result<()> {
+        let backend = MemoryBackend::new();
+        let policy = RetryPolicy::new()
+            .max_attempts(3)
+            .base_delay(Duration::from_millis(1));
+        let deta = Deta::in_memory(backend.clone());
+        let base = deta.with_retry_policy(policy.clone()).base("test");
+
+        backend.fail_next(2, StatusCode::SERVICE_UNAVAILABLE);
+        base.put(Item::new_with_key("a", 1usize)).await?;
+        assert_eq!(base.get::<usize>("a").await?, 1);
+
+        backend.fail_next(3, StatusCode::TOO_MANY_REQUESTS);
+        let result = base.get::<usize>("a").await;
+        assert_eq!(
+            result.unwrap_err().status(),
+            Some(StatusCode::TOO_MANY_REQUESTS)
+        );
+
+        backend.fail_next(1, StatusCode::BAD_GATEWAY);
+        let result = base.insert(Item::new_with_key("b", 2usize)).await;
+        assert!(result.unwrap_err().is_retryable());
+
+        let base = deta
+            .with_retry_policy(policy.retry_non_idempotent(true))
+            .base("test");
+        backend.fail_next(1, StatusCode::BAD_GATEWAY);
+        base.insert(Item::new_with_key("b", 2usize)).await?;
+
+        backend.fail_next(1, StatusCode::BAD_REQUEST);
+        let result = base.put(Item::new_with_key("c", 3usize)).await;
+        assert!(matches!(result, Err(Error::BadRequest(_))));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn typed_base() -> anyhow::Result<()> {
+        let deta = Deta::in_memory(MemoryBackend::new());
+        let users = deta.typed_base::<User>("users");
+
+        let user = User {
+            name: "Jimmy".to_string(),
+            age: 32,
+            likes: Vec::new(),
+        };
+        users.insert(Item::new_with_key("jimmy", user)).await?;
+        users
+            .update("jimmy", Update::new().increment("age", 1))
+            .await?;
+
+        let user = users.get("jimmy").await?;
+        assert_eq!(user.age, 33);
+
+        let items = users.query(Query::new().equal("name", "Jimmy")).await?;
+        assert_eq!(items[0].key.as_deref(), Some("jimmy"));
+
+        let base = users.into_base();
+        let user: User = base.get("jimmy").await?;
+        assert_eq!(user.name, "Jimmy");
+
+        let nameless = serde_json::json!({ "likes": [] });
+        base.put(Item::new_with_key("nameless", nameless)).await?;
+        let result = base.get::<User>("nameless").await;
+        assert!(matches!(result, Err(Error::JSONDeserializingFailed(_))));
+        let source = std::error::Error::source(result.as_ref().unwrap_err()).unwrap();
+        assert_eq!(source.to_string(), "missing field `name`");
+
+        let tags = vec![((1, 2), true)].into_iter().collect::<HashMap<_, _>>();
+        let result = base.update("jimmy", Update::new().set("tags", tags)).await;
+        assert!(matches!(result, Err(Error::JSONSerializingFailed(_))));
+        let source = std::error::Error::source(result.as_ref().unwrap_err()).unwrap();
+        assert_eq!(source.to_string(), "tags: key must be a string");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn get_item_envelope() -> anyhow::Result<()> {
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Name {
+            name: String,
+        }
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Counter {
+            value: u32,
+        }
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Wrapper {
+            value: serde_json::Value,
+        }
+
+        let deta = Deta::in_memory(MemoryBackend::new());
+        let base = deta.base("test");
+
+        let name = Name {
+            name: "Jimmy".to_string(),
+        };
+        base.put(Item::new_with_key("name", name)).await?;
+        let item: Item<Name> = base.get_item("name").await?;
+        assert_eq!(item.key.as_deref(), Some("name"));
+        assert_eq!(item.value.name, "Jimmy");
+
+        base.put(Item::new_with_key("counter", Counter { value: 5 }))
+            .await?;
+        assert_eq!(base.get::<Counter>("counter").await?, Counter { value: 5 });
+        assert_eq!(base.get::<u32>("counter").await?, 5);
+
+        // The inner object could be read as a `Wrapper` too, but the attributes are read as one.
+        let wrapper = Wrapper {
+            value: serde_json::json!({ "value": 1 }),
+        };
+        base.put(Item::new_with_key("wrapper", wrapper)).await?;
+        let item: Item<Wrapper> = base.get_item("wrapper").await?;
+        assert_eq!(item.value.value, serde_json::json!({ "value": 1 }));
+        let value: serde_json::Value = base.get("wrapper").await?;
+        assert_eq!(value, serde_json::json!({ "value": 1 }));
+
+        base.put(Item::new_with_key("none", None::<u32>)).await?;
+        assert_eq!(base.get::<Option<u32>>("none").await?, None);
+
+        base.put(Item::new_with_key("list", vec![1, 2])).await?;
+        let item: Item<Vec<u32>> = base.get_item("list").await?;
+        assert_eq!(item, Item::new_with_key("list", vec![1, 2]));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn absent_fields() -> anyhow::Result<()> {
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Profile {
+            name: String,
+            nickname: Option<String>,
+        }
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Stats {
+            name: String,
+            #[serde(default)]
+            visits: u32,
+        }
+
+        let deta = Deta::in_memory(MemoryBackend::new());
+        let base = deta.base("test");
+
+        let name = serde_json::json!({ "name": "Jimmy" });
+        base.put(Item::new_with_key("jimmy", name)).await?;
+
+        let profile = Profile {
+            name: "Jimmy".to_string(),
+            nickname: None,
+        };
+        assert_eq!(base.get::<Profile>("jimmy").await?, profile);
+        let items = base.query::<Profile>(Query::new()).await?;
+        assert_eq!(items, vec![Item::new_with_key("jimmy", profile)]);
+
+        let stats = Stats {
+            name: "Jimmy".to_string(),
+            visits: 0,
+        };
+        assert_eq!(base.get::<Stats>("jimmy").await?, stats);
+        let items = base.query::<Stats>(Query::new()).await?;
+        assert_eq!(items, vec![Item::new_with_key("jimmy", stats)]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn get_opt_failures() -> anyhow::Result<()> {
+        let backend = MemoryBackend::new();
+        let deta = Deta::in_memory(backend.clone()).with_retry_policy(RetryPolicy::never());
+        let base = deta.base("test");
+
+        base.put(Item::new_with_key("a", 1usize)).await?;
+        assert_eq!(base.get_opt::<usize>("a").await?, Some(1));
+        assert_eq!(base.get_opt::<usize>("b").await?, None);
+
+        backend.fail_next(1, StatusCode::UNAUTHORIZED);
+        let result = base.get_opt::<usize>("a").await;
+        assert!(matches!(result, Err(Error::Unauthorized(_))));
+
+        backend.fail_next(1, StatusCode::FORBIDDEN);
+        let result = base.get::<usize>("a").await;
+        assert!(matches!(result, Err(Error::Forbidden(_))));
+
+        backend.fail_next(1, StatusCode::TOO_MANY_REQUESTS);
+        let result = base.get_opt::<usize>("a").await;
+        assert!(matches!(result, Err(Error::TooManyRequests(_))));
+
+        backend.fail_next(1, StatusCode::INTERNAL_SERVER_ERROR);
+        let result = base.get_opt::<usize>("a").await;
+        assert!(matches!(result, Err(Error::ServerError(_))));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn delete_failures() -> anyhow::Result<()> {
+        let backend = MemoryBackend::new();
+        let deta = Deta::in_memory(backend.clone()).with_retry_policy(RetryPolicy::never());
+        let base = deta.base("test");
+
+        base.put(Item::new_with_key("a", 1usize)).await?;
+        base.put(Item::new_with_key("b", 2usize)).await?;
+
+        backend.fail_next(1, StatusCode::UNAUTHORIZED);
+        let result = base.delete("a").await;
+        assert!(matches!(result, Err(Error::Unauthorized(_))));
+
+        backend.fail_next(1, StatusCode::SERVICE_UNAVAILABLE);
+        let result = base.delete("a").await;
+        assert!(matches!(result, Err(Error::ServerError(_))));
+        assert_eq!(base.get::<usize>("a").await?, 1);
+
+        base.delete("a").await?;
+        base.delete("a").await?;
+        assert_eq!(base.get_opt::<usize>("a").await?, None);
+
+        assert!(base.delete_existing("b").await?);
+        assert!(!base.delete_existing("b").await?);
+
+        backend.fail_next(1, StatusCode::FORBIDDEN);
+        let result = base.delete_existing("b").await;
+        assert!(matches!(result, Err(Error::Forbidden(_))));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn keys_are_encoded() -> anyhow::Result<()> {
+        let deta = Deta::in_memory(MemoryBackend::new());
+        let base = deta.base("my base/1");
+
+        let keys = [
+            "jimmy@example.com",
+            "a/b/c",
+            "what? #1",
+            "50%",
+            "émoji 🦀",
+            "..a",
+        ];
+        for (i, key) in keys.iter().enumerate() {
+            base.put(Item::new_with_key(*key, i)).await?;
+        }
+
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(base.get::<usize>(key).await?, i);
+            base.update(key, Update::new().increment("value", 1))
+                .await?;
+            assert_eq!(base.get::<usize>(key).await?, i + 1);
+            assert!(base.delete_existing(key).await?);
+        }
+        assert!(deta
+            .base("my base")
+            .query::<usize>(Query::new())
+            .await?
+            .is_empty());
+
+        for key in &["", ".", ".."] {
+            let result = base.get::<usize>(key).await;
+            assert!(matches!(result, Err(Error::InvalidItemKey(x)) if x == *key));
+
+            let result = base.put(Item::new_with_key(*key, 1usize)).await;
+            assert!(matches!(result, Err(Error::InvalidItemKey(_))));
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn put_rejected() -> anyhow::Result<()> {
+        let backend = MemoryBackend::new();
+        let base = Deta::in_memory(backend.clone()).base("test");
+
+        backend.reject_next(1);
+        let result = base.put(Item::new_with_key("a", 1usize)).await;
+        match result {
+            Err(Error::ItemRejected(x)) => assert_eq!(x["key"], "a"),
+            x => panic!("expected a rejected item, got {:?}", x),
+        }
+        assert_eq!(base.get_opt::<usize>("a").await?, None);
+
+        backend.reject_next(2);
+        let items = vec![
+            Item::new_with_key("a", 1),
+            Item::new_with_key("b", 2),
+            Item::new(3),
+        ];
+        let report = base.put_many(items).await?;
+        assert_eq!(report.processed(), ["a"]);
+        // The memory backend, like Deta, doesn't send a reason for the failed items.
+        assert!(report.failed().iter().all(|x| x.reason.is_none()));
+        let failed = report.into_failed_items();
+        assert_eq!(failed, vec![Item::new_with_key("b", 2), Item::new(3)]);
+
+        let report = base.put_many(failed).await?;
+        assert_eq!(report.processed().len(), 2);
+        assert!(report.is_complete());
+        assert_eq!(base.get::<usize>("b").await?, 2);
+
+        let policy = RetryPolicy::new()
+            .base_delay(Duration::from_millis(1))
+            .retry_failed_items(true);
+        let base = base.typed::<usize>();
+        let retrying = Deta::in_memory(backend.clone())
+            .with_retry_policy(policy)
+            .typed_base::<usize>("test");
+
+        backend.reject_next(1);
+        let items = vec![Item::new_with_key("c", 3), Item::new_with_key("d", 4)];
+        let report = retrying.put_all(items.clone(), 1).await?;
+        assert_eq!(report.processed(), ["c", "d"]);
+
+        backend.reject_next(5);
+        let report = retrying.put_all(items.clone(), 1).await?;
+        assert_eq!(report.processed(), ["c"]);
+        assert_eq!(report.failed().len(), 1);
+        assert_eq!(report.failed()[0].item, items[1]);
+
+        backend.reject_next(1);
+        let report = base.put_many(items).await?;
+        assert_eq!(report.failed().len(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn put_unmatched() -> anyhow::Result<()> {
+        /// Sends back a failed item which wasn't sent.
+        struct Unmatched;
+
+        impl Transport for Unmatched {
+            fn send(
+                &self,
+                _: Request<Bytes>,
+            ) -> BoxFuture<'static, Result<Response<Body>, TransportError>> {
+                let body = serde_json::json!({
+                    "processed": { "items": [{ "key": "a", "name": "A", "age": 1, "likes": [] }] },
+                    "failed": { "items": [{ "key": "changed", "name": "B" }] },
+                });
+                let mut response = Response::new(Body::from(body.to_string().into_bytes()));
+                *response.status_mut() = StatusCode::MULTI_STATUS;
+                Box::pin(futures::future::ready(Ok(response)))
+            }
+        }
+
+        let deta = DetaBuilder::new("abc_secret")
+            .transport(Unmatched)
+            .build()?;
+        let user = |name: &str, age| User {
+            name: name.to_string(),
+            age,
+            likes: Vec::new(),
+        };
+
+        // `User` isn't `Clone`, and doesn't need to be.
+        let items = vec![
+            Item::new_with_key("a", user("A", 1)),
+            Item::new_with_key("b", user("B", 2)),
+        ];
+        let report = deta.base("test").put_many(items).await?;
+
+        assert_eq!(report.processed(), ["a"]);
+        assert!(report.failed().is_empty());
+        assert_eq!(report.unmatched()[0]["key"], "changed");
+        assert!(!report.is_complete());
+
+        let (processed, failed, unmatched, errors) = report.into_parts();
+        assert_eq!(processed, ["a"]);
+        assert!(failed.is_empty() && errors.is_empty());
+        assert_eq!(
+            unmatched,
+            [serde_json::json!({ "key": "changed", "name": "B" })]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn size_limits() -> anyhow::Result<()> {
+        let deta = Deta::in_memory(MemoryBackend::new());
+        let base = deta.base("test");
+
+        let large = "a".repeat(400 * 1024);
+        let result = base.put(Item::new_with_key("large", large.clone())).await;
+        match result {
+            Err(Error::ItemTooLarge { key, size }) => {
+                assert_eq!(key.as_deref(), Some("large"));
+                assert!(size > 400 * 1024);
+            }
+            x => panic!("expected a large item, got {:?}", x),
+        }
+
+        let result = base.insert(Item::new(large)).await;
+        assert!(matches!(result, Err(Error::ItemTooLarge { key: None, .. })));
+
+        let items = (0..3)
+            .map(|x| Item::new_with_key(x, x % 2))
+            .chain(vec![Item::new_with_key(1, 5)])
+            .collect::<Vec<_>>();
+        let result = base.put_many(items).await;
+        assert!(matches!(result, Err(Error::DuplicateKey(x)) if x == "1"));
+
+        let items = (0..100)
+            .map(|x| Item::new_with_key(x % 60, x))
+            .collect::<Vec<_>>();
+        let result = base.put_all(items, 4).await;
+        assert!(matches!(result, Err(Error::DuplicateKey(x)) if x == "0"));
+        assert!(base.query::<usize>(Query::new()).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn put_all_batches() -> anyhow::Result<()> {
+        /// Records the number of items of each put sent to the memory backend.
+        #[derive(Clone, Default)]
+        struct Batches {
+            backend: MemoryBackend,
+            sizes: Arc<Mutex<Vec<usize>>>,
+        }
+
+        impl Transport for Batches {
+            fn send(
+                &self,
+                request: Request<Bytes>,
+            ) -> BoxFuture<'static, Result<Response<Body>, TransportError>> {
+                if request.method() == "PUT" {
+                    let body: serde_json::Value = serde_json::from_slice(request.body()).unwrap();
+                    let size = body["items"].as_array().unwrap().len();
+                    self.sizes.lock().unwrap().push(size);
+                }
+
+                serve(&self.backend, request)
+            }
+        }
+
+        let batches = Batches::default();
+        let deta = DetaBuilder::new("abc_secret")
+            .endpoint("http://localhost/v1")
+            .transport(batches.clone())
+            .build()?;
+        let base = deta.base("test");
+        let sizes = || {
+            let mut sizes = batches.sizes.lock().unwrap().split_off(0);
+            sizes.sort_unstable();
+            sizes
+        };
+
+        let items = (0..60).map(|x| Item::new_with_key(x, x)).collect();
+        assert!(base.put_all(items, 4).await?.is_complete());
+        assert_eq!(sizes(), [10, 25, 25]);
+
+        // Items can't be larger than 400 KB, so 25 of them never reach the 16 MB of a request,
+        // and the largest items are still sent 25 at a time.
+        let large = "a".repeat(400 * 1024 - 100);
+        let items = (0..26)
+            .map(|x| Item::new_with_key(x, large.clone()))
+            .collect();
+        assert!(base.put_all(items, 4).await?.is_complete());
+        assert_eq!(sizes(), [1, 25]);
+
+        // A failed batch doesn't stop the others, and keeps its items and its error.
+        batches.backend.fail_next(1, StatusCode::BAD_REQUEST);
+        let items = (0..60)
+            .map(|x| Item::new_with_key(format!("b{}", x), x))
+            .collect();
+        let report = base.put_all(items, 1).await?;
+        assert!(!report.is_complete());
+        assert_eq!(report.processed().len(), 35);
+        assert!(matches!(report.errors(), [Error::BadRequest(_)]));
+        let failed = report.into_failed_items();
+        assert_eq!(failed.len(), 25);
+        let key = failed[0].key.as_deref().unwrap();
+        assert_eq!(base.get_opt::<usize>(key).await?, None);
+        assert_eq!(sizes(), [10, 25, 25]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn custom_transport() -> anyhow::Result<()> {
+        #[derive(Clone, Default)]
+        struct Recorder {
+            backend: MemoryBackend,
+            requests: Arc<Mutex<Vec<String>>>,
+        }
+
+        impl Transport for Recorder {
+            fn send(
+                &self,
+                request: Request<Bytes>,
+            ) -> BoxFuture<'static, Result<Response<Body>, TransportError>> {
+                let key = request.headers()["x-api-key"].to_str().unwrap_or_default();
+                let line = format!("{} {} {}", request.method(), request.uri(), key);
+                self.requests.lock().unwrap().push(line);
+
+                serve(&self.backend, request)
+            }
+        }
+
+        let recorder = Recorder::default();
+        let deta = DetaBuilder::new("abc_secret")
+            .endpoint("http://localhost/v1")
+            .transport(recorder.clone())
+            .build()?;
+        let base = deta.base("my base");
+
+        base.put(Item::new_with_key("a/b", 1usize)).await?;
+        assert_eq!(base.get::<usize>("a/b").await?, 1);
+
+        let requests = recorder.requests.lock().unwrap().clone();
+        assert_eq!(
+            requests,
+            vec![
+                "PUT http://localhost/v1/abc/my%20base/items abc_secret",
+                "GET http://localhost/v1/abc/my%20base/items/a%2Fb abc_secret",
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn cassette() -> anyhow::Result<()> {
+        let path = |name: &str| {
+            let name = format!("deta_{}_memory_{}.json", std::process::id(), name);
+            std::env::temp_dir().join(name)
+        };
+        let (path, redacted) = (path("cassette"), path("cassette_redacted"));
+
+        let backend = MemoryBackend::new();
+        let deta = Deta::in_memory(backend.clone()).recording(&path)?;
+        let base = deta.base("test");
+
+        base.insert(Item::new_with_key("a", 1usize)).await?;
+        let result = base.insert(Item::new_with_key("a", 2usize)).await;
+        assert!(matches!(result, Err(Error::KeyConflict(_))));
+        backend.fail_next(1, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(base.get::<usize>("a").await?, 1);
+
+        // The memory backend isn't used while replaying.
+        let deta = Deta::in_memory(MemoryBackend::new()).replaying(&path)?;
+        let base = deta.base("test");
+
+        base.insert(Item::new_with_key("a", 1usize)).await?;
+        let result = base.insert(Item::new_with_key("a", 2usize)).await;
+        assert!(matches!(result, Err(Error::KeyConflict(_))));
+        assert_eq!(base.get::<usize>("a").await?, 1);
+
+        let result = base.get::<usize>("a").await;
+        assert!(matches!(result, Err(Error::RequestSendError(_))));
+        assert!(!result.as_ref().unwrap_err().is_retryable());
+        let source = std::error::Error::source(result.as_ref().unwrap_err().request().unwrap());
+        assert!(source
+            .unwrap()
+            .to_string()
+            .contains("GET memory://base/test/items/a"));
+
+        std::fs::remove_file(&path)?;
+
+        #[derive(Clone, Default)]
+        struct Project(MemoryBackend);
+
+        impl Transport for Project {
+            fn send(
+                &self,
+                request: Request<Bytes>,
+            ) -> BoxFuture<'static, Result<Response<Body>, TransportError>> {
+                serve(&self.0, request)
+            }
+        }
+
+        let deta = DetaBuilder::new("abc_secret")
+            .endpoint("http://localhost/v1")
+            .transport(Project::default())
+            .build()?
+            .recording(&redacted)?;
+        let base = deta.base("test");
+        assert_eq!(base.put(Item::new_with_key("a", 1usize)).await?, "a");
+        assert_eq!(base.get::<usize>("a").await?, 1);
+
+        let json = std::fs::read_to_string(&redacted)?;
+        assert!(json.contains("[redacted]"));
+        assert!(!json.contains("abc_secret"));
+
+        // The key isn't recorded, so another key of the project replays the same requests.
+        let deta = DetaBuilder::new("abc_other")
+            .endpoint("http://localhost/v1")
+            .transport(Project::default())
+            .build()?
+            .replaying(&redacted)?;
+        let base = deta.base("test");
+        assert_eq!(base.put(Item::new_with_key("a", 1usize)).await?, "a");
+        assert_eq!(base.get::<usize>("a").await?, 1);
+        std::fs::remove_file(&redacted)?;
+
+        Ok(())
+    }
+}